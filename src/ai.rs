@@ -1,39 +1,665 @@
-use crate::{eval::Recommendation, state::Position};
-
-pub fn decide(
-    position: Position,
-    hand_strength: f64,
-    pot_odds: f64,
-    num_opponents: usize,
-    call_price: usize,
-    pot: usize,
-    stack: usize,
-    blind: usize,
-) -> (Recommendation, usize) {
-    let hs2 = hand_strength.powi(2);
-    let bet_size = if call_price == 0 {
-        (pot.next_multiple_of(blind)).min(stack)
-    } else {
-        ((call_price * 2).next_multiple_of(blind)).min(stack)
-    };
-    let raise_amount = bet_size - call_price;
-    let fold_ev = 0.0;
-    let call_ev = (pot as f64 * hand_strength) - (call_price as f64 * (1.0 - hand_strength));
-    // Average pot size after raise
-    let raise_pot = (1..=num_opponents).fold(0, |total, calls| {
-        total + pot + (calls * raise_amount) + bet_size
-    }) as f64
-        / (num_opponents as f64);
-    let raise_ev = (raise_pot * hs2) - (bet_size as f64) * (1.0 - hs2);
-    if raise_ev > call_ev && raise_ev > fold_ev {
-        if bet_size >= stack {
-            (Recommendation::AllIn, stack)
+use crate::{
+    cards::{Card, HoleCards, Variant},
+    draws::Draws,
+    eval::Recommendation,
+    plan::Commitment,
+    range::FoldModel,
+    state::*,
+};
+
+/// Share of its equity a hand realizes when it acts last on later streets
+const IN_POSITION_REALIZATION: f64 = 1.05;
+/// Share of its equity a hand realizes when it acts first on later streets
+const OUT_OF_POSITION_REALIZATION: f64 = 0.85;
+/// Chance that a single player left to act raises behind a call
+const RAISE_BEHIND: f64 = 0.05;
+/// Equity below which a hand has too little showdown value to win by checking
+const SHOWDOWN_VALUE: f64 = 0.3;
+/// Share of the pot bet on each later street
+const FUTURE_BET: f64 = 0.5;
+/// Chance that a player with a strong hand gets paid off by a worse one on later streets
+const PAID_OFF: f64 = 0.5;
+
+/// Expected value of calling `call_price`
+pub fn call_ev(hand_strength: f64, call_price: usize, pot: usize) -> f64 {
+    (pot as f64 * hand_strength) - (call_price as f64 * (1.0 - hand_strength))
+}
+
+/// Chips expected to change hands on later streets after a call
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ImpliedOdds {
+    /// Winnings when a draw completes
+    pub implied: f64,
+    /// Losses when a made hand turns out to be second best
+    pub reverse: f64,
+}
+
+/// Chance of hitting one of `outs` on the cards still to come on `board` when holding `hand`
+pub fn hit_chance(outs: usize, variant: Variant, hand: &[Card], board: Board) -> f64 {
+    let to_come = 5 - board.into_iter().count();
+    let unseen = variant
+        .deck_without(hand.iter().copied().chain(board))
+        .len();
+    let miss = (0..to_come).fold(1.0, |miss, i| {
+        miss * unseen.saturating_sub(outs + i) as f64 / (unseen - i) as f64
+    });
+    1.0 - miss
+}
+
+/// Share of its range a player must continue with against a bet of `bet` into `pot`, so that
+/// bluffing with any two cards does not profit
+pub fn minimum_defense(bet: usize, pot: usize) -> f64 {
+    pot as f64 / (pot + bet) as f64
+}
+
+/// Share of the time a bet of `bet` into `pot` must win immediately to break even as a bluff
+pub fn alpha(bet: usize, pot: usize) -> f64 {
+    bet as f64 / (pot + bet) as f64
+}
+
+/// Everything known about a spot the hero has to act in
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spot {
+    pub hand: HoleCards,
+    pub board: Board,
+    /// Equity against every opponent still in the hand
+    pub hand_strength: f64,
+    pub call_price: usize,
+    /// Chips the hero already has in front of them on this street, such as a blind
+    pub put_in: usize,
+    pub pot: usize,
+    pub stack: usize,
+    /// Smaller of the hero's stack and the largest opponent stack
+    pub effective_stack: usize,
+    pub blind: usize,
+    /// Whether the hero acts after every remaining opponent on later streets
+    pub in_position: bool,
+    /// Opponents still to act on this street after the hero
+    pub left_to_act: usize,
+    /// How each opponent still in the hand responds to a bet
+    pub opponents: Vec<FoldModel>,
+    pub variant: Variant,
+}
+
+/// The expected value of one option
+#[derive(Debug, Clone, PartialEq)]
+pub struct Choice {
+    pub recommendation: Recommendation,
+    /// Chips put in the pot
+    pub amount: usize,
+    pub ev: f64,
+    pub explanation: String,
+}
+
+impl std::fmt::Display for Choice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:+.0}: {}", self.ev, self.explanation)
+    }
+}
+
+/// Why a hand is bet in a balanced strategy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Value,
+    Bluff,
+    Check,
+}
+
+/// How often to bet a hand on the river so the betting range has the right mix of value
+/// bets and bluffs for the size
+#[derive(Debug, Clone, PartialEq)]
+pub struct Balance {
+    pub amount: usize,
+    pub minimum_defense: f64,
+    pub alpha: f64,
+    /// Chance that every opponent folds to the bet
+    pub fold_chance: f64,
+    /// Shares of the calling hands and of the folding hands that the hero's cards block
+    pub blockers: (f64, f64),
+    pub role: Role,
+    /// Share of the time to bet this hand
+    pub frequency: f64,
+}
+
+impl std::fmt::Display for Balance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "MDF {:.0}%, alpha {:.0}%, expected folds {:.0}%",
+            self.minimum_defense * 100.0,
+            self.alpha * 100.0,
+            self.fold_chance * 100.0
+        )?;
+        writeln!(
+            f,
+            "{:.2} bluffs per value bet, blocking {:.0}% of calls and {:.0}% of folds",
+            // Bluffing this often leaves a caller indifferent
+            self.alpha,
+            self.blockers.0 * 100.0,
+            self.blockers.1 * 100.0
+        )?;
+        match self.role {
+            Role::Value => write!(f, "Bet {} for value", self.amount),
+            Role::Bluff => write!(
+                f,
+                "Bluff {} {:.0}% of the time",
+                self.amount,
+                self.frequency * 100.0
+            ),
+            Role::Check => write!(f, "Check, too much showdown value to bluff"),
+        }
+    }
+}
+
+/// The best option in a spot, along with every option considered
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advice {
+    pub recommendation: Recommendation,
+    pub amount: usize,
+    pub choices: Vec<Choice>,
+    /// Balanced betting frequency on the river
+    pub balance: Option<Balance>,
+}
+
+impl Spot {
+    /// Share of its equity the hero can expect to realize by seeing later streets
+    pub fn realization(&self) -> f64 {
+        if self.board.street() == Street::River {
+            return 1.0;
+        }
+        let position = if self.in_position {
+            IN_POSITION_REALIZATION
+        } else {
+            OUT_OF_POSITION_REALIZATION
+        };
+        position * (1.0 - RAISE_BEHIND).powi(self.left_to_act as i32)
+    }
+
+    /// Chance that every opponent folds to the hero putting `amount` in the pot. Opponents
+    /// face what the hero puts in beyond a call, bet into the pot after the call.
+    pub fn fold_chance(&self, amount: usize) -> f64 {
+        if self.opponents.is_empty() {
+            return 0.0;
+        }
+        let raise = amount.saturating_sub(self.call_price);
+        self.opponents
+            .iter()
+            .map(|o| o.fold_chance(raise, self.pot + self.call_price))
+            .product()
+    }
+
+    /// Equity when called after opponents fold `folds` of their range.
+    /// Opponents fold their weakest hands, so it lies between the equity against the whole
+    /// range and the equity if every folded hand had been beaten.
+    fn called_strength(&self, folds: f64) -> f64 {
+        if folds >= 1.0 {
+            return self.hand_strength;
+        }
+        let beaten = ((self.hand_strength - folds) / (1.0 - folds)).max(0.0);
+        (self.hand_strength + beaten) / 2.0
+    }
+
+    /// Balanced frequency for betting `amount` with this hand.
+    /// The hero's range is taken to hold hands whose equity is spread evenly from 0 to 1,
+    /// so value bets are the hands ahead of the calling range, and bluffs come from the hands
+    /// without showdown value, preferring those that block calls rather than folds.
+    pub fn balance(&self, amount: usize) -> Balance {
+        let raise = amount.saturating_sub(self.call_price);
+        // Opponents face the raise bet into the pot after the hero's call
+        let pot = self.pot + self.call_price;
+        let fold_chance = self.fold_chance(amount);
+        let alpha = alpha(raise, pot);
+        let opponents = self.opponents.len().max(1) as f64;
+        let blockers = self.opponents.iter().fold((0.0, 0.0), |total, o| {
+            let (calls, folds) = o.blocked(self.hand.cards(), raise, pot);
+            (total.0 + calls / opponents, total.1 + folds / opponents)
+        });
+        let (role, frequency) = if self.called_strength(fold_chance) > 0.5 {
+            (Role::Value, 1.0)
+        } else if self.hand_strength < SHOWDOWN_VALUE {
+            let value = (1.0 - fold_chance) / 2.0;
+            let bluffs = value * alpha;
+            let preference = if blockers.0 + blockers.1 > 0.0 {
+                1.0 + (blockers.0 - blockers.1) / (blockers.0 + blockers.1)
+            } else {
+                1.0
+            };
+            (
+                Role::Bluff,
+                (bluffs / SHOWDOWN_VALUE * preference).clamp(0.0, 1.0),
+            )
+        } else {
+            (Role::Check, 0.0)
+        };
+        Balance {
+            amount,
+            minimum_defense: minimum_defense(raise, pot),
+            alpha,
+            fold_chance,
+            blockers,
+            role,
+            frequency,
+        }
+    }
+
+    /// Future winnings and losses from calling, with a bet of half the pot on each later
+    /// street, capped by the effective stack.
+    /// Before the flop only pocket pairs drawing to a set are counted.
+    pub fn implied_odds(&self) -> ImpliedOdds {
+        let board = self.board.into_iter().collect::<Vec<_>>();
+        let streets = match self.board.street() {
+            Street::PreFlop => 3,
+            Street::Flop => 2,
+            Street::Turn => 1,
+            Street::River => return ImpliedOdds::default(),
+        };
+        let behind = self.effective_stack.saturating_sub(self.call_price) as f64;
+        let pot = (self.pot + 2 * self.call_price) as f64;
+        let future = (pot * FUTURE_BET * streets as f64).min(behind) * PAID_OFF;
+        let hand = self.hand.cards();
+        let pocket_pair = hand
+            .iter()
+            .enumerate()
+            .any(|(i, a)| hand[i + 1..].iter().any(|b| b.0 == a.0));
+        let outs = match (self.board, self.hand.pair()) {
+            (Board::PreFlop, _) if pocket_pair => 2,
+            (_, Some(pair)) => Draws::find(self.variant, pair, &board).outs(),
+            // Omaha draws aren't counted
+            (_, None) => 0,
+        };
+        // A pair using a hole card is strong enough to keep calling when behind
+        let made = self.board != Board::PreFlop
+            && (pocket_pair || hand.iter().any(|h| board.iter().any(|b| b.0 == h.0)));
+        ImpliedOdds {
+            implied: hit_chance(outs, self.variant, hand, self.board) * future,
+            reverse: if made {
+                (1.0 - self.hand_strength) * future
+            } else {
+                0.0
+            },
+        }
+    }
+
+    pub fn fold(&self) -> Choice {
+        Choice {
+            recommendation: Recommendation::Fold,
+            amount: 0,
+            ev: 0.0,
+            explanation: "fold, nothing more is lost".to_string(),
+        }
+    }
+
+    pub fn call(&self) -> Choice {
+        let realized = (self.hand_strength * self.realization()).min(1.0);
+        let odds = self.implied_odds();
+        Choice {
+            recommendation: Recommendation::Call,
+            amount: self.call_price,
+            ev: call_ev(realized, self.call_price, self.pot) + odds.implied - odds.reverse,
+            explanation: format!(
+                "{} {}, realizing {:.0}% equity {}, {:+.0} implied, {:+.0} reverse implied",
+                if self.call_price == 0 {
+                    "check"
+                } else {
+                    "call"
+                },
+                self.call_price,
+                realized * 100.0,
+                if self.in_position {
+                    "in position"
+                } else {
+                    "out of position"
+                },
+                odds.implied,
+                -odds.reverse
+            ),
+        }
+    }
+
+    /// Expected value of putting `amount` in the pot, winning it outright when everyone folds
+    pub fn raise(&self, amount: usize) -> Choice {
+        let amount = amount.min(self.stack);
+        let folds = self.fold_chance(amount);
+        let called_strength = self.called_strength(folds);
+        let realized = if amount >= self.stack {
+            called_strength
         } else {
-            (Recommendation::Raise, bet_size)
+            (called_strength * self.realization()).min(1.0)
+        };
+        let raise = amount.saturating_sub(self.call_price);
+        let called_pot = self.pot + amount + raise;
+        let called_ev = realized * called_pot as f64 - amount as f64;
+        Choice {
+            recommendation: if amount >= self.stack {
+                Recommendation::AllIn
+            } else {
+                Recommendation::Raise
+            },
+            amount,
+            ev: folds * self.pot as f64 + (1.0 - folds) * called_ev,
+            explanation: format!(
+                "{} {amount}, {:.0}% to take the pot of {} uncontested, otherwise {:.0}% equity \
+                 in a pot of {called_pot}",
+                if amount >= self.stack {
+                    "all in for"
+                } else if self.call_price == 0 {
+                    "bet"
+                } else {
+                    "raise with"
+                },
+                folds * 100.0,
+                self.pot,
+                realized * 100.0
+            ),
+        }
+    }
+}
+
+/// A bet or raise size the engine can consider
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BetSize {
+    /// Fraction of the pot after calling
+    Pot(f64),
+    /// Smallest legal raise, or a bet of one blind
+    MinRaise,
+    /// Multiple of the bet faced, or of the blind when opening pre-flop
+    Times(f64),
+    AllIn,
+}
+
+impl std::fmt::Display for BetSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BetSize::Pot(fraction) if *fraction == 1.0 => write!(f, "pot"),
+            BetSize::Pot(fraction) => write!(f, "{:.0}% pot", fraction * 100.0),
+            BetSize::MinRaise => write!(f, "min-raise"),
+            BetSize::Times(times) => write!(f, "{times}x"),
+            BetSize::AllIn => write!(f, "all in"),
+        }
+    }
+}
+
+impl BetSize {
+    pub const MENU: [BetSize; 8] = [
+        BetSize::Pot(0.33),
+        BetSize::Pot(0.5),
+        BetSize::Pot(0.75),
+        BetSize::Pot(1.0),
+        BetSize::Pot(1.5),
+        BetSize::MinRaise,
+        BetSize::Times(3.0),
+        BetSize::AllIn,
+    ];
+
+    /// Chips the hero puts in the pot for this size, if it is a legal raise in `spot`
+    pub fn amount(self, spot: &Spot) -> Option<usize> {
+        let blind = spot.blind.max(1);
+        let amount = match self {
+            BetSize::Pot(fraction) => {
+                spot.call_price + (fraction * (spot.pot + spot.call_price) as f64) as usize
+            }
+            BetSize::MinRaise => spot.call_price + spot.call_price.max(blind),
+            BetSize::Times(times) => {
+                // A multiple of the opponent's whole bet on the street, at least the big blind
+                // before the flop
+                let facing = spot.put_in + spot.call_price;
+                let facing = match spot.board {
+                    Board::PreFlop => facing.max(blind),
+                    _ if facing == 0 => return None,
+                    _ => facing,
+                };
+                ((times * facing as f64) as usize).saturating_sub(spot.put_in)
+            }
+            BetSize::AllIn => spot.stack,
+        };
+        // In pot-limit games a raise may be at most the size of the pot after calling
+        let limit = if spot.variant.pot_limit() {
+            spot.call_price + spot.pot + spot.call_price
+        } else {
+            spot.stack
+        };
+        let amount = amount.next_multiple_of(blind).min(limit).min(spot.stack);
+        (amount > spot.call_price).then_some(amount)
+    }
+}
+
+/// Compare folding, calling and raising by every size in `menu`, recommending the option with
+/// the highest EV. Committed hands get all in, and hands in pot control avoid big bets.
+pub fn decide(spot: &Spot, menu: &[BetSize]) -> Advice {
+    let mut choices = vec![];
+    if spot.call_price > 0 {
+        choices.push(spot.fold());
+    }
+    if spot.call_price < spot.stack || spot.call_price == 0 {
+        choices.push(spot.call());
+    }
+    let mut amounts = vec![];
+    for size in menu {
+        // Sizes that put the same chips in, such as a big raise capped by the stack, are
+        // only listed once
+        if let Some(amount) = size.amount(spot)
+            && !amounts.contains(&amount)
+        {
+            amounts.push(amount);
+            let mut choice = spot.raise(amount);
+            choice.explanation = format!("{size}: {}", choice.explanation);
+            choices.push(choice);
+        }
+    }
+    if spot.call_price >= spot.stack && spot.stack > 0 {
+        choices.push(spot.raise(spot.stack));
+    }
+    let commitment = spot.commitment();
+    let all_in = choices
+        .iter()
+        .find(|c| c.recommendation == Recommendation::AllIn);
+    let best = match (commitment, all_in) {
+        (Commitment::Committed, Some(all_in)) => Some(all_in),
+        // Pot-limit stacks go in with the biggest raise allowed
+        (Commitment::Committed, None) if spot.variant.pot_limit() => choices
+            .iter()
+            .filter(|c| c.recommendation >= Recommendation::Raise)
+            .max_by_key(|c| c.amount),
+        (Commitment::PotControl, _) => choices
+            .iter()
+            .filter(|c| c.amount <= spot.call_price + spot.pot / 2)
+            .max_by(|a, b| a.ev.total_cmp(&b.ev)),
+        _ => choices.iter().max_by(|a, b| a.ev.total_cmp(&b.ev)),
+    }
+    .cloned()
+    .unwrap_or_else(|| spot.fold());
+    // Balance the most profitable size
+    let balance = choices
+        .iter()
+        .filter(|c| c.recommendation >= Recommendation::Raise)
+        .max_by(|a, b| a.ev.total_cmp(&b.ev))
+        .filter(|_| spot.board.street() == Street::River)
+        .map(|c| spot.balance(c.amount));
+    Advice {
+        recommendation: best.recommendation,
+        amount: best.amount,
+        choices,
+        balance,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cards::Card,
+        range::{Profile, Range},
+    };
+
+    fn spot(board: Board, in_position: bool) -> Spot {
+        let hand = HoleCards::from(cards!("Ah", "Kh"));
+        let opponent = Range::full()
+            .without(hand.cards().iter().copied().chain(board))
+            .fold_model(&Profile::default(), board, Variant::Holdem);
+        Spot {
+            hand,
+            board,
+            hand_strength: 0.5,
+            call_price: 0,
+            put_in: 0,
+            pot: 100,
+            stack: 1000,
+            effective_stack: 1000,
+            blind: 2,
+            in_position,
+            left_to_act: 0,
+            opponents: vec![opponent],
+            variant: Variant::Holdem,
+        }
+    }
+
+    fn flop() -> Board {
+        Board::from_cards(&cards!("Qs", "7d", "2c")).unwrap()
+    }
+
+    /// Opponents see a pot sized bet as a bet the size of the pot, and acting last realizes
+    /// more equity
+    #[test]
+    fn position_and_fold_equity() {
+        let spot = spot(flop(), true);
+        assert_eq!(
+            spot.fold_chance(100),
+            spot.opponents[0].fold_chance(100, 100)
+        );
+        assert!(spot.fold_chance(50) < spot.fold_chance(100));
+        let out_of_position = Spot {
+            in_position: false,
+            ..spot.clone()
+        };
+        assert!(spot.realization() > out_of_position.realization());
+        assert!(spot.call().ev > out_of_position.call().ev);
+    }
+
+    /// Sizes relative to the pot after calling, capped by the stack and by the pot in
+    /// pot-limit games, with each distinct amount evaluated once
+    #[test]
+    fn bet_sizes() {
+        let opening = spot(flop(), true);
+        let amounts = BetSize::MENU.map(|size| size.amount(&opening));
+        assert_eq!(
+            amounts,
+            [
+                Some(34),
+                Some(50),
+                Some(76),
+                Some(100),
+                Some(150),
+                Some(2),
+                None,
+                Some(1000)
+            ]
+        );
+        let facing = Spot {
+            call_price: 20,
+            pot: 120,
+            ..opening.clone()
+        };
+        assert_eq!(BetSize::Pot(1.0).amount(&facing), Some(160));
+        assert_eq!(BetSize::MinRaise.amount(&facing), Some(40));
+        assert_eq!(BetSize::Times(3.0).amount(&facing), Some(60));
+        // Three times a raise to 30 over the hero's bet of 10, and over a raise to 6 from the
+        // big blind
+        let reraising = Spot {
+            put_in: 10,
+            pot: 130,
+            ..facing.clone()
+        };
+        assert_eq!(BetSize::Times(3.0).amount(&reraising), Some(80));
+        let big_blind = Spot {
+            board: Board::PreFlop,
+            call_price: 4,
+            put_in: 2,
+            pot: 9,
+            ..opening.clone()
+        };
+        assert_eq!(BetSize::Times(3.0).amount(&big_blind), Some(16));
+        let pot_limit = Spot {
+            variant: Variant::Omaha,
+            ..facing.clone()
+        };
+        assert_eq!(BetSize::AllIn.amount(&pot_limit), Some(160));
+        assert_eq!(BetSize::Pot(1.5).amount(&pot_limit), Some(160));
+        let advice = decide(&pot_limit, &BetSize::MENU);
+        let raises = advice
+            .choices
+            .iter()
+            .filter(|c| c.recommendation >= Recommendation::Raise)
+            .map(|c| c.amount)
+            .collect::<Vec<_>>();
+        assert_eq!(raises, [66, 90, 126, 160, 40, 60]);
+    }
+
+    /// A pot sized bet must be defended half the time and work as a bluff half the time
+    #[test]
+    fn pot_sized_balance() {
+        let river = Board::from_cards(&cards!("Qs", "7d", "2c", "9h", "3s")).unwrap();
+        let bluff = Spot {
+            hand_strength: 0.1,
+            ..spot(river, true)
+        };
+        let balance = bluff.balance(100);
+        assert_eq!((balance.minimum_defense, balance.alpha), (0.5, 0.5));
+        assert_eq!(balance.role, Role::Bluff);
+        assert!(balance.frequency > 0.0 && balance.frequency <= 1.0);
+        let value = Spot {
+            hand_strength: 0.95,
+            ..bluff.clone()
+        };
+        assert_eq!(value.balance(100).role, Role::Value);
+        // Facing a bet, only the chips beyond the call are bet into the pot after calling
+        let raising = Spot {
+            call_price: 50,
+            pot: 150,
+            ..bluff
+        };
+        let balance = raising.balance(250);
+        assert_eq!((balance.minimum_defense, balance.alpha), (0.5, 0.5));
+    }
+
+    /// Draws win more on later streets when they hit, and made hands lose more when behind
+    #[test]
+    fn implied_odds() {
+        let hand = cards!("Ah", "Kh");
+        let hit = |variant, hand: &[Card]| hit_chance(9, variant, hand, flop());
+        assert!((hit(Variant::Holdem, &hand) - (1.0 - 38.0 / 47.0 * 37.0 / 46.0)).abs() < 1e-12);
+        // Fewer unseen cards in a short deck, more hole cards in Omaha
+        let short = Board::from_cards(&cards!("Qs", "7d", "6c")).unwrap();
+        assert!(
+            (hit_chance(9, Variant::ShortDeck, &hand, short) - (1.0 - 22.0 / 31.0 * 21.0 / 30.0))
+                .abs()
+                < 1e-12
+        );
+        let omaha = cards!("Ah", "Kh", "Jd", "Tc");
+        assert!((hit(Variant::Omaha, &omaha) - (1.0 - 36.0 / 45.0 * 35.0 / 44.0)).abs() < 1e-12);
+        let flush_draw = Spot {
+            board: Board::from_cards(&cards!("Qh", "7h", "2c")).unwrap(),
+            hand_strength: 0.35,
+            call_price: 50,
+            pot: 150,
+            ..spot(flop(), true)
+        };
+        let odds = flush_draw.implied_odds();
+        assert!(odds.implied > 0.0);
+        assert_eq!(odds.reverse, 0.0);
+        let top_pair = Spot {
+            board: Board::from_cards(&cards!("Ks", "7d", "2c")).unwrap(),
+            hand_strength: 0.8,
+            ..flush_draw.clone()
+        };
+        let odds = top_pair.implied_odds();
+        assert!(odds.reverse > 0.0);
+        // Nothing is left to win or lose after the river
+        let river = Board::from_cards(&cards!("Ks", "7d", "2c", "9h", "3s")).unwrap();
+        let odds = Spot {
+            board: river,
+            ..top_pair
         }
-    } else if call_ev > fold_ev || hand_strength > pot_odds || call_price == 0 {
-        (Recommendation::Call, call_price)
-    } else {
-        (Recommendation::Fold, 0)
+        .implied_odds();
+        assert_eq!(odds, ImpliedOdds::default());
     }
 }
@@ -0,0 +1,139 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{cards::Variant, eval::SpotKey};
+
+/// Equities calculated in earlier sessions, kept in a file that each new result is appended to
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EquityCache {
+    entries: HashMap<SpotKey, f64>,
+    path: PathBuf,
+}
+
+impl EquityCache {
+    /// An empty cache saving to `path`
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        EquityCache {
+            entries: HashMap::new(),
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Equities saved in tab separated columns, one spot per line. Lines that can't be read,
+    /// such as one cut short by a crash, are left out and the file is written again without
+    /// them, so that later results aren't appended to a broken line.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut this = Self::new(&path);
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(this),
+            Err(e) => return Err(e),
+        };
+        let mut invalid = !text.is_empty() && !text.ends_with('\n');
+        for line in text.lines() {
+            match parse_line(line) {
+                Some((key, strength)) => {
+                    this.entries.insert(key, strength);
+                }
+                None => invalid = true,
+            }
+        }
+        if invalid {
+            let text = this
+                .entries
+                .iter()
+                .map(|(key, strength)| format_line(key, *strength))
+                .collect::<String>();
+            std::fs::write(&this.path, text)?;
+        }
+        Ok(this)
+    }
+
+    pub fn get(&self, key: &SpotKey) -> Option<f64> {
+        self.entries.get(key).copied()
+    }
+
+    /// Remember the equity of a spot and append it to the file
+    pub fn insert(&mut self, key: SpotKey, strength: f64) -> io::Result<()> {
+        let line = format_line(&key, strength);
+        self.entries.insert(key, strength);
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(line.as_bytes())
+    }
+}
+
+/// A spot and its equity as a line of the cache file
+fn format_line(key: &SpotKey, strength: f64) -> String {
+    format!(
+        "{}\t{}\t{}\t{:016x}\t{strength}\n",
+        key.variant,
+        key.rounds
+            .iter()
+            .map(|r| r.to_string())
+            .collect::<Vec<_>>()
+            .join(","),
+        key.index,
+        key.range
+    )
+}
+
+/// A spot and its equity from a line of the cache file
+fn parse_line(line: &str) -> Option<(SpotKey, f64)> {
+    let [variant, rounds, index, range, strength] =
+        line.split('\t').collect::<Vec<_>>().try_into().ok()?;
+    let key = SpotKey {
+        variant: Variant::ALL
+            .into_iter()
+            .find(|v| v.to_string() == variant)?,
+        rounds: rounds
+            .split(',')
+            .map(|r| r.parse::<usize>())
+            .collect::<Result<_, _>>()
+            .ok()?,
+        index: index.parse().ok()?,
+        range: u64::from_str_radix(range, 16).ok()?,
+    };
+    Some((key, strength.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(index: usize) -> SpotKey {
+        SpotKey {
+            variant: Variant::Holdem,
+            rounds: vec![2, 3],
+            index,
+            range: 0xfeed,
+        }
+    }
+
+    /// Results survive a reload, and a broken line only loses itself
+    #[test]
+    fn reload() {
+        let path = std::env::temp_dir().join(format!("equity-{}.tsv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut cache = EquityCache::load(&path).unwrap();
+        cache.insert(key(1), 0.25).unwrap();
+        cache.insert(key(2), 0.75).unwrap();
+        assert_eq!(EquityCache::load(&path).unwrap(), cache);
+
+        // A write cut short leaves a line without its end
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, format!("not an equity\n{text}Hold'em\t2,3")).unwrap();
+        let mut reloaded = EquityCache::load(&path).unwrap();
+        assert_eq!(reloaded, cache);
+        reloaded.insert(key(3), 0.5).unwrap();
+        let reloaded_again = EquityCache::load(&path).unwrap();
+        assert_eq!(reloaded_again, reloaded);
+        assert_eq!(reloaded_again.get(&key(3)), Some(0.5));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
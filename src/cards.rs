@@ -14,24 +14,109 @@ macro_rules! cards {
     }
 }
 
+/// Game being played, deciding the cards in the deck and how hands rank
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub enum Variant {
+    #[default]
+    Holdem,
+    /// 36 card deck without the twos to fives, where a flush beats a full house
+    ShortDeck,
+    /// Pot-limit Omaha with four or five hole cards, playing exactly two of them
+    Omaha,
+    /// Pot-limit Omaha where the best eight-or-better low wins half the pot
+    OmahaHiLo,
+}
+
+impl std::fmt::Display for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Variant::Holdem => "Hold'em",
+                Variant::ShortDeck => "Short deck",
+                Variant::Omaha => "PLO",
+                Variant::OmahaHiLo => "PLO8",
+            }
+        )
+    }
+}
+
+impl Variant {
+    pub const ALL: [Variant; 4] = [
+        Variant::Holdem,
+        Variant::ShortDeck,
+        Variant::Omaha,
+        Variant::OmahaHiLo,
+    ];
+
+    pub fn faces(self) -> &'static [Face] {
+        match self {
+            Variant::Holdem | Variant::Omaha | Variant::OmahaHiLo => &Face::ALL,
+            Variant::ShortDeck => &Face::ALL[4..],
+        }
+    }
+
+    /// Numbers of hole cards each player may be dealt
+    pub fn hole_card_counts(self) -> &'static [usize] {
+        match self {
+            Variant::Holdem | Variant::ShortDeck => &[2],
+            Variant::Omaha | Variant::OmahaHiLo => &[4, 5],
+        }
+    }
+
+    /// Whether hands play exactly two hole cards and three from the board
+    pub fn omaha(self) -> bool {
+        matches!(self, Variant::Omaha | Variant::OmahaHiLo)
+    }
+
+    /// Whether bets are capped at the size of the pot
+    pub fn pot_limit(self) -> bool {
+        self.omaha()
+    }
+
+    /// Whether the pot is split between the best high and the best qualifying low
+    pub fn hi_lo(self) -> bool {
+        self == Variant::OmahaHiLo
+    }
+
+    pub fn deck(self) -> Vec<Card> {
+        self.faces()
+            .iter()
+            .copied()
+            .cartesian_product(Suite::ALL)
+            .map(|(face, suite)| Card(face, suite))
+            .collect::<Vec<_>>()
+    }
+
+    pub fn deck_without(self, cards: impl IntoIterator<Item = Card>) -> Vec<Card> {
+        let cards = cards.into_iter().collect::<Vec<_>>();
+        self.deck()
+            .into_iter()
+            .filter(|c| !cards.contains(c))
+            .collect()
+    }
+
+    /// Cards of a full deck that are not used in this variant
+    pub fn missing_cards(self) -> Vec<Card> {
+        deck_without(self.deck())
+    }
+}
+
+/// Full 52 card deck
 pub fn deck() -> Vec<Card> {
-    Face::ALL
-        .into_iter()
-        .cartesian_product(Suite::ALL)
-        .map(|(face, suite)| Card(face, suite))
-        .collect::<Vec<_>>()
+    Variant::Holdem.deck()
 }
 
 pub fn deck_without(cards: impl IntoIterator<Item = Card>) -> Vec<Card> {
-    let cards = cards.into_iter().collect::<Vec<_>>();
-    deck().into_iter().filter(|c| !cards.contains(c)).collect()
+    Variant::Holdem.deck_without(cards)
 }
 
 pub fn unique_open_hands() -> Vec<[Card; 2]> {
     let mut cards = vec![];
     for f1 in Face::ALL {
         for f2 in Face::ALL {
-            cards.push(if f1 <= f2 {
+            cards.push(if f1.high() <= f2.high() {
                 [Card(f1, Suite::Heart), Card(f2, Suite::Spade)]
             } else {
                 [Card(f1, Suite::Heart), Card(f2, Suite::Heart)]
@@ -80,7 +165,9 @@ impl Suite {
     pub const ALL: [Suite; 4] = [Self::Heart, Self::Spade, Self::Diamond, Self::Club];
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+/// Card face. Faces have no order of their own, as the ace plays both high and low, so they
+/// compare through `high`, `low` or `cmp_high`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Face {
     Ace = 1,
     Two = 2,
@@ -158,11 +245,158 @@ impl Face {
         Self::King,
         Self::Ace,
     ];
+
+    /// Value with the ace above the king, as it plays in most hands
+    pub fn high(self) -> usize {
+        match self {
+            Face::Ace => 14,
+            face => face as usize,
+        }
+    }
+
+    /// Value with the ace below the two, as it plays in a wheel and in low hands
+    pub fn low(self) -> usize {
+        self as usize
+    }
+
+    /// Compare faces with the ace high
+    pub fn cmp_high(self, other: Face) -> std::cmp::Ordering {
+        self.high().cmp(&other.high())
+    }
+
+    /// The face with a high or low value
+    pub fn from_value(value: usize) -> Option<Face> {
+        match value {
+            1 | 14 => Some(Face::Ace),
+            2..=13 => Some(Face::ALL[value - 2]),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Face::Two => "Two",
+            Face::Three => "Three",
+            Face::Four => "Four",
+            Face::Five => "Five",
+            Face::Six => "Six",
+            Face::Seven => "Seven",
+            Face::Eight => "Eight",
+            Face::Nine => "Nine",
+            Face::Ten => "Ten",
+            Face::Jack => "Jack",
+            Face::Queen => "Queen",
+            Face::King => "King",
+            Face::Ace => "Ace",
+        }
+    }
+
+    pub fn plural(self) -> String {
+        match self {
+            Face::Six => "Sixes".to_string(),
+            face => format!("{}s", face.name()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Card(pub Face, pub Suite);
 
+impl Card {
+    /// Unique index of this card in `0..52`
+    pub fn index(self) -> usize {
+        (self.0 as usize - 1) * 4 + (self.1 as usize - 1)
+    }
+
+    pub fn from_index(index: usize) -> Self {
+        Card(Face::ALL[(index / 4 + 12) % 13], Suite::ALL[index % 4])
+    }
+}
+
+/// Set of cards, one bit per `Card::index`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct CardSet(u64);
+
+impl CardSet {
+    pub fn insert(&mut self, card: Card) {
+        self.0 |= 1 << card.index();
+    }
+}
+
+impl FromIterator<Card> for CardSet {
+    fn from_iter<T: IntoIterator<Item = Card>>(iter: T) -> Self {
+        let mut set = CardSet::default();
+        for card in iter {
+            set.insert(card);
+        }
+        set
+    }
+}
+
+impl IntoIterator for CardSet {
+    type Item = Card;
+
+    type IntoIter = <Vec<Card> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        (0..52)
+            .filter(|i| self.0 & 1 << i != 0)
+            .map(Card::from_index)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+/// Cards dealt face down to one player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HoleCards {
+    /// Unused slots repeat the first card
+    cards: [Card; 5],
+    len: usize,
+}
+
+impl HoleCards {
+    pub const MAX: usize = 5;
+
+    /// Between two and five hole cards
+    pub fn new(cards: &[Card]) -> Option<Self> {
+        if !(2..=Self::MAX).contains(&cards.len()) {
+            return None;
+        }
+        let mut all = [cards[0]; Self::MAX];
+        all[..cards.len()].copy_from_slice(cards);
+        Some(HoleCards {
+            cards: all,
+            len: cards.len(),
+        })
+    }
+
+    pub fn cards(&self) -> &[Card] {
+        &self.cards[..self.len]
+    }
+
+    /// The hole cards of a hold'em hand
+    pub fn pair(&self) -> Option<[Card; 2]> {
+        self.cards().try_into().ok()
+    }
+}
+
+impl From<[Card; 2]> for HoleCards {
+    fn from(cards: [Card; 2]) -> Self {
+        HoleCards::new(&cards).unwrap()
+    }
+}
+
+impl IntoIterator for HoleCards {
+    type Item = Card;
+
+    type IntoIter = <Vec<Card> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.cards().to_vec().into_iter()
+    }
+}
+
 impl std::fmt::Display for Card {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}{}", self.0, self.1)
@@ -196,7 +430,7 @@ pub struct Hand(pub [Card; 5]);
 impl std::fmt::Display for Hand {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut faces = self.faces();
-        faces.sort();
+        faces.sort_by(|a, b| b.cmp_high(*a));
         write!(
             f,
             "{}{}{}{}{} [{}]",
@@ -232,8 +466,9 @@ impl Hand {
         self.0.map(|c| c.0)
     }
 
+    /// Face values with the ace high
     pub fn face_values(&self) -> [usize; 5] {
-        self.0.map(|c| c.0 as usize)
+        self.0.map(|c| c.0.high())
     }
 
     pub fn faces_iter(&self) -> impl Iterator<Item = Face> {
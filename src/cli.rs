@@ -0,0 +1,307 @@
+use crate::{
+    cards::*,
+    eval::{Recommendation, matchups_or_calculate},
+    history::*,
+    matchups::Matchups,
+    parse::Parse,
+    preflop::*,
+    range::Range,
+    review,
+    state::*,
+    stats::Tracker,
+    storage,
+    stud::*,
+};
+
+const USAGE: &str = "usage: holdem [import|review <hand history files>...]
+       holdem prewarm
+       holdem allin <hero range> <villain range>
+       holdem pushfold <stack in big blinds> [calling range]
+       holdem composition <range> <board cards>
+       holdem stud <stud|razz|stud8> <small bet> <down/up cards> <opponent up cards>... \
+[dead=<cards>]
+prewarm, allin and pushfold calculate the preflop matchup table the first time, which takes
+about an hour on a single core";
+
+/// Random deals used to estimate stud equity
+const STUD_SAMPLES: usize = 20000;
+
+/// Run a command given on the command line, returning the exit code
+pub fn run(args: &[String]) -> i32 {
+    match args {
+        [command, files @ ..] if command == "import" && !files.is_empty() => import(files),
+        [command, files @ ..] if command == "review" && !files.is_empty() => review(files),
+        [command, args @ ..] if command == "stud" && args.len() >= 4 => stud(args),
+        [command] if command == "prewarm" => prewarm(),
+        [command, hero, villain] if command == "allin" => all_in(hero, villain),
+        [command, stack, calling @ ..] if command == "pushfold" && calling.len() <= 1 => {
+            push_fold(stack, calling.first())
+        }
+        [command, range, board] if command == "composition" => composition(range, board),
+        _ => {
+            eprintln!("{USAGE}");
+            2
+        }
+    }
+}
+
+/// Read hand histories and add every player's actions to the opponent statistics
+fn import(files: &[String]) -> i32 {
+    let path = storage::data_path("opponents.tsv");
+    let mut tracker = match Tracker::load(&path) {
+        Ok(tracker) => tracker,
+        Err(e) => {
+            eprintln!("{}: {e}", path.display());
+            return 1;
+        }
+    };
+    let mut hands = 0;
+    for file in files {
+        match read_histories(file) {
+            Ok(histories) => {
+                for hand in &histories {
+                    tracker.record_hand(&hand.actions, hand.hero.as_deref());
+                }
+                hands += histories.len();
+            }
+            Err(e) => {
+                eprintln!("{file}: {e}");
+                return 1;
+            }
+        }
+    }
+    if let Err(e) = tracker.save(&path) {
+        eprintln!("{}: {e}", path.display());
+        return 1;
+    }
+    println!(
+        "Imported {hands} hands, tracking {} players",
+        tracker.players.len()
+    );
+    0
+}
+
+/// Compare the hero's decisions with the engine and summarise the biggest leaks
+fn review(files: &[String]) -> i32 {
+    let path = storage::data_path("opponents.tsv");
+    let tracker = match Tracker::load(&path) {
+        Ok(tracker) => tracker,
+        Err(e) => {
+            eprintln!("{}: {e}", path.display());
+            return 1;
+        }
+    };
+    let mut reviews = vec![];
+    for file in files {
+        match read_histories(file) {
+            Ok(histories) => reviews.extend(
+                histories
+                    .iter()
+                    .filter_map(|hand| review::review_hand(hand, &tracker)),
+            ),
+            Err(e) => {
+                eprintln!("{file}: {e}");
+                return 1;
+            }
+        }
+    }
+    for review in &reviews {
+        for decision in review.decisions.iter().filter(|d| d.deviates()) {
+            println!("Hand #{} {decision}", review.hand.id);
+        }
+    }
+    let decisions = reviews.iter().map(|r| r.decisions.len()).sum::<usize>();
+    println!("Reviewed {} hands, {decisions} decisions", reviews.len());
+    for leak in review::leaks(&reviews) {
+        println!(
+            "{} ({} times, EV loss {:.0})",
+            leak.description, leak.count, leak.ev_loss
+        );
+    }
+    0
+}
+
+/// Show who acts first and the hero's equity in a stud hand
+fn stud(args: &[String]) -> i32 {
+    let parse_cards = |s: &str| Vec::<Card>::parse(&mut s.chars().filter(|c| !c.is_whitespace()));
+    let [game, small_bet, hero, rest @ ..] = args else {
+        unreachable!()
+    };
+    let (dead, opponents): (Vec<_>, Vec<_>) = rest.iter().partition(|a| a.starts_with("dead="));
+    let parsed = (
+        StudGame::parse(&mut game.chars()),
+        small_bet.parse::<usize>().ok(),
+        StudHand::parse(&mut hero.chars()),
+        opponents
+            .iter()
+            .map(|o| parse_cards(o))
+            .collect::<Option<Vec<_>>>(),
+        dead.iter()
+            .map(|d| parse_cards(&d["dead=".len()..]))
+            .collect::<Option<Vec<_>>>(),
+    );
+    let (Some(game), Some(small_bet), Some(hero), Some(opponents), Some(dead)) = parsed else {
+        eprintln!("{USAGE}");
+        return 2;
+    };
+    let Some(street) = hero.street() else {
+        eprintln!("stud hands have between three and seven cards");
+        return 2;
+    };
+    let hands = std::iter::once(hero.clone())
+        .chain(
+            opponents
+                .into_iter()
+                .map(|up| StudHand { down: vec![], up }),
+        )
+        .collect::<Vec<_>>();
+    let seat = |i: usize| {
+        if i == 0 {
+            "hero".to_string()
+        } else {
+            format!("seat {i}")
+        }
+    };
+    let limits = StudLimits::with_small_bet(small_bet);
+    println!("{game}, antes of {}", limits.ante);
+    if street == StudStreet::Third {
+        if let Some(player) = game.bring_in(&hands) {
+            println!(
+                "{street}: {} brings in for {}, completing to {}",
+                seat(player),
+                limits.bring_in,
+                limits.small_bet
+            );
+        }
+    } else if let Some(player) = game.first_to_act(&hands) {
+        let open_pair = hands
+            .iter()
+            .any(|h| matches!(h.up[..], [a, b] if a.0 == b.0));
+        println!(
+            "{street}: {} acts first, bets of {}",
+            seat(player),
+            limits.bet(game, street, open_pair)
+        );
+    }
+    if game != StudGame::Razz && hero.cards().count() >= 5 {
+        let rank = Variant::Holdem.best_rank_in(hero.cards());
+        let class = rank.class().map(|class| format!(" (class {class})"));
+        println!("Hero has {rank}{}", class.unwrap_or_default());
+    }
+    let equity = game.equity(
+        &hero,
+        &hands[1..],
+        dead.into_iter().flatten().collect(),
+        STUD_SAMPLES,
+    );
+    match game {
+        StudGame::StudHiLo => println!("Equity: {:.1}% ({equity})", equity.total() * 100.0),
+        _ => println!("Equity: {:.1}%", equity.total() * 100.0),
+    }
+    0
+}
+
+/// Calculate the preflop matchup table so that later sessions find it ready, then show every
+/// starting hand's equity against the default opponent ranges
+fn prewarm() -> i32 {
+    matchups();
+    let hands = unique_open_hands();
+    for variance in [Variance::Random, Variance::Normal] {
+        for (i, hand) in hands.iter().enumerate() {
+            let state = DeckState {
+                board: Board::PreFlop,
+                hand: (*hand).into(),
+                variance,
+                dead: CardSet::default(),
+                variant: Variant::Holdem,
+            };
+            println!(
+                "{}/{} {} against {} opponents: {:.1}%",
+                i + 1,
+                hands.len(),
+                OpeningHand::from(*hand),
+                match variance {
+                    Variance::Random => "random",
+                    Variance::Normal => "typical",
+                },
+                state.strength() * 100.0
+            );
+        }
+    }
+    0
+}
+
+/// The preflop matchup table, calculating and saving it when it's missing
+fn matchups() -> &'static Matchups {
+    let mut calculated = false;
+    let matchups = matchups_or_calculate(|done, total| {
+        calculated = true;
+        eprint!("\rCalculating preflop matchups: {done}/{total} boards");
+    });
+    if calculated {
+        eprintln!();
+    }
+    matchups
+}
+
+/// A range of hand classes such as `22+,ATs+,KQo`
+fn parse_range(text: &str) -> Option<Range> {
+    // One class at a time, as `HandChart::new` only keeps a last class that ends in a plus
+    text.split(',')
+        .map(|class| {
+            let chars = &mut class.strip_suffix('+').unwrap_or(class).chars();
+            let hand = OpeningHand::parse(chars).filter(|_| chars.next().is_none())?;
+            Some(if class.ends_with('+') {
+                HandChart::new(Recommendation::Call, class)
+            } else {
+                let mut chart = HandChart::default();
+                chart[hand] = Recommendation::Call;
+                chart
+            })
+        })
+        .try_fold(HandChart::default(), |chart, class| Some(chart | class?))
+        .map(Range::from)
+}
+
+/// Show the equity of one range against another when all in preflop
+fn all_in(hero: &str, villain: &str) -> i32 {
+    let (Some(hero_range), Some(villain_range)) = (parse_range(hero), parse_range(villain)) else {
+        eprintln!("{USAGE}");
+        return 2;
+    };
+    let matchups = matchups();
+    println!(
+        "{hero} against {villain}: {:.1}%",
+        matchups.range_equity(&hero_range, &villain_range) * 100.0
+    );
+    0
+}
+
+/// Show the hands to move all in with from the small blind, heads up against a big blind who
+/// calls with `calling`, or with the usual opponent range
+fn push_fold(stack: &str, calling: Option<&String>) -> i32 {
+    let calling = match calling {
+        Some(calling) => parse_range(calling),
+        None => Some(Range::from(HandChart::opponent_expectation())),
+    };
+    let (Ok(stack), Some(calling)) = (stack.parse::<f64>(), calling) else {
+        eprintln!("{USAGE}");
+        return 2;
+    };
+    let matchups = matchups();
+    print!("{}", matchups.shoving_chart(stack, &calling));
+    0
+}
+
+/// Show the made hands and draws a range holds on a flop, turn or river
+fn composition(range: &str, board: &str) -> i32 {
+    let board = Vec::<Card>::parse(&mut board.chars().filter(|c| !c.is_whitespace()))
+        .and_then(|cards| Board::from_cards(&cards))
+        .filter(|board| *board != Board::PreFlop);
+    let (Some(range), Some(board)) = (parse_range(range), board) else {
+        eprintln!("{USAGE}");
+        return 2;
+    };
+    print!("{}", range.composition(board));
+    0
+}
@@ -0,0 +1,243 @@
+use itertools::Itertools;
+
+use crate::{cards::*, draws::Draws, range::Range, rank::*, state::*};
+
+/// Kickers ranked among the best faces not on the board that still count as good
+const GOOD_KICKERS: usize = 3;
+
+/// How well the other hole card plays alongside top pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Kicker {
+    Weak,
+    /// One of the few best faces not on the board
+    Good,
+    /// The best face not on the board
+    Top,
+}
+
+/// Best made hand or draw a combination holds on a board, weakest first. Made hands only count
+/// when the hole cards play, and draws only count for hands without a pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Holding {
+    Air,
+    Gutshot,
+    OpenEnded,
+    FlushDraw,
+    /// A pocket pair below the top card of the board or a hole card pairing a lower card
+    WeakPair,
+    TopPair(Kicker),
+    Overpair,
+    /// Both hole cards pairing the board
+    TwoPairs,
+    /// One hole card matching a pair on the board
+    Trips,
+    /// A pocket pair matching a card on the board
+    Set,
+    Straight,
+    Flush,
+    FullHouse,
+    FourKind,
+    StraightFlush,
+}
+
+impl std::fmt::Display for Holding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Holding::Air => "Air",
+                Holding::Gutshot => "Gutshot",
+                Holding::OpenEnded => "Open-ended straight draw",
+                Holding::FlushDraw => "Flush draw",
+                Holding::WeakPair => "Weak pair",
+                Holding::TopPair(Kicker::Weak) => "Top pair, weak kicker",
+                Holding::TopPair(Kicker::Good) => "Top pair, good kicker",
+                Holding::TopPair(Kicker::Top) => "Top pair, top kicker",
+                Holding::Overpair => "Overpair",
+                Holding::TwoPairs => "Two pairs",
+                Holding::Trips => "Trips",
+                Holding::Set => "Set",
+                Holding::Straight => "Straight",
+                Holding::Flush => "Flush",
+                Holding::FullHouse => "Full house",
+                Holding::FourKind => "Four of a kind",
+                Holding::StraightFlush => "Straight flush",
+            }
+        )
+    }
+}
+
+impl Holding {
+    /// What `hand` holds on a flop, turn or river `board`
+    pub fn find(hand: [Card; 2], board: &[Card]) -> Self {
+        let rank = Variant::Holdem.best_rank_for(&hand, board);
+        // With five cards on the board the hole cards may not play at all
+        let plays = board.len() < 5 || rank > Variant::Holdem.best_rank_in(board.iter().copied());
+        let on_board = |face: Face| board.iter().filter(|c| c.0 == face).count();
+        let pocket_pair = hand[0].0 == hand[1].0;
+        let top = board.iter().map(|c| c.0.high()).max().unwrap_or_default();
+        let paired = hand
+            .iter()
+            .filter(|c| on_board(c.0) > 0)
+            .map(|c| c.0)
+            .dedup()
+            .collect::<Vec<_>>();
+        match rank.kind() {
+            RankKind::StraightFlush | RankKind::RoyalFlush if plays => {
+                return Holding::StraightFlush;
+            }
+            RankKind::FourKind if plays => return Holding::FourKind,
+            RankKind::FullHouse if plays => return Holding::FullHouse,
+            RankKind::Flush if plays => return Holding::Flush,
+            RankKind::Straight if plays => return Holding::Straight,
+            _ => {}
+        }
+        if pocket_pair && on_board(hand[0].0) > 0 {
+            return Holding::Set;
+        }
+        if paired.iter().any(|face| on_board(*face) >= 2) {
+            return Holding::Trips;
+        }
+        if paired.len() == 2 {
+            return Holding::TwoPairs;
+        }
+        if pocket_pair && hand[0].0.high() > top {
+            return Holding::Overpair;
+        }
+        if let [face] = paired[..] {
+            if face.high() < top {
+                return Holding::WeakPair;
+            }
+            let kicker = hand.iter().find(|c| c.0 != face).unwrap().0;
+            // Faces that would make a better kicker
+            let better = Face::ALL
+                .iter()
+                .filter(|f| f.high() > kicker.high() && on_board(**f) == 0)
+                .count();
+            return Holding::TopPair(match better {
+                0 => Kicker::Top,
+                n if n < GOOD_KICKERS => Kicker::Good,
+                _ => Kicker::Weak,
+            });
+        }
+        if pocket_pair {
+            return Holding::WeakPair;
+        }
+        let draws = Draws::find(Variant::Holdem, hand, board);
+        if draws.flush_draw {
+            Holding::FlushDraw
+        } else if draws.open_ended {
+            Holding::OpenEnded
+        } else if draws.gutshot {
+            Holding::Gutshot
+        } else {
+            Holding::Air
+        }
+    }
+}
+
+/// Weighted combinations of a range holding each kind of made hand or draw on a board
+#[derive(Debug, Clone, PartialEq)]
+pub struct Composition {
+    /// Each holding in the range with its combinations, the strongest first
+    pub holdings: Vec<(Holding, f64)>,
+    pub total: f64,
+}
+
+impl Composition {
+    /// Share of the range's combinations with `holding`
+    pub fn share(&self, holding: Holding) -> f64 {
+        let combos = self
+            .holdings
+            .iter()
+            .filter(|(h, _)| *h == holding)
+            .fold(0.0, |sum, (_, combos)| sum + combos);
+        if self.total == 0.0 {
+            0.0
+        } else {
+            combos / self.total
+        }
+    }
+}
+
+impl std::fmt::Display for Composition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (holding, combos) in &self.holdings {
+            writeln!(
+                f,
+                "{holding}: {:.1}% ({combos:.1} combos)",
+                self.share(*holding) * 100.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Range {
+    /// Made hands and draws the range holds on `board`, leaving out combinations that share a
+    /// card with it. Empty before the flop.
+    pub fn composition(&self, board: Board) -> Composition {
+        let board_cards = board.into_iter().collect::<Vec<_>>();
+        if board_cards.len() < 3 {
+            return Composition {
+                holdings: vec![],
+                total: 0.0,
+            };
+        }
+        let holdings = self
+            .clone()
+            .without(board_cards.iter().copied())
+            .combos()
+            .map(|(hand, weight)| (Holding::find(hand, &board_cards), weight))
+            .into_grouping_map()
+            .fold(0.0, |sum, _, weight| sum + weight)
+            .into_iter()
+            .sorted_by(|a, b| b.0.cmp(&a.0))
+            .collect::<Vec<_>>();
+        let total = holdings.iter().fold(0.0, |sum, (_, combos)| sum + combos);
+        Composition { holdings, total }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eval::Recommendation, preflop::HandChart};
+
+    /// Made hands by how the hole cards play, and draws only without a pair
+    #[test]
+    fn holdings() {
+        let board = cards!("Ks", "7d", "2c");
+        let find = |hand| Holding::find(hand, &board);
+        assert_eq!(find(cards!("Ah", "Kh")), Holding::TopPair(Kicker::Top));
+        assert_eq!(find(cards!("Kh", "Qh")), Holding::TopPair(Kicker::Good));
+        assert_eq!(find(cards!("Kh", "5h")), Holding::TopPair(Kicker::Weak));
+        assert_eq!(find(cards!("As", "Ad")), Holding::Overpair);
+        assert_eq!(find(cards!("7h", "7c")), Holding::Set);
+        assert_eq!(find(cards!("Kh", "7h")), Holding::TwoPairs);
+        assert_eq!(find(cards!("7h", "3h")), Holding::WeakPair);
+        assert_eq!(find(cards!("9h", "8h")), Holding::Air);
+        let ten = cards!("Ts", "7d", "2c");
+        assert_eq!(Holding::find(cards!("9h", "8h"), &ten), Holding::OpenEnded);
+        assert_eq!(
+            Holding::find(cards!("Ah", "2h"), &cards!("Kh", "7h", "2c", "Ac", "2d")),
+            Holding::FullHouse
+        );
+        // The board's own straight doesn't count for the hole cards
+        assert_eq!(
+            Holding::find(cards!("2h", "3h"), &cards!("Ks", "Qd", "Jc", "Th", "9s")),
+            Holding::Air
+        );
+
+        let range = Range::from(HandChart::new(Recommendation::Call, "KK+"));
+        let composition = range.composition(Board::from_cards(&board).unwrap());
+        assert_eq!(
+            composition.holdings,
+            [(Holding::Set, 3.0), (Holding::Overpair, 6.0)]
+        );
+        assert_eq!(composition.share(Holding::Overpair), 6.0 / 9.0);
+        assert_eq!(composition.share(Holding::Air), 0.0);
+        assert_eq!(range.composition(Board::PreFlop).holdings, []);
+    }
+}
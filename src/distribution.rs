@@ -0,0 +1,339 @@
+use itertools::Itertools;
+use rand::seq::IndexedRandom;
+use rayon::prelude::*;
+
+use crate::{cards::*, eval::*, range::Range, rank::Ranking, state::*};
+
+/// Lowest equity that crushes an opponent, and the highest that is crushed
+const CRUSHING: f64 = 0.75;
+const CRUSHED: f64 = 0.25;
+
+/// Lowest and highest equity of a flip
+const FLIP: std::ops::RangeInclusive<f64> = 0.4..=0.6;
+
+/// How equity spreads over the combinations of a range
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EquityDistribution {
+    /// Equity and weight of every combination, lowest equity first
+    pub equities: Vec<(f64, f64)>,
+}
+
+impl EquityDistribution {
+    pub fn new(equities: impl IntoIterator<Item = (f64, f64)>) -> Self {
+        let mut equities = equities.into_iter().collect::<Vec<_>>();
+        equities.sort_by(|a, b| a.0.total_cmp(&b.0));
+        EquityDistribution { equities }
+    }
+
+    /// Share of the weight of each tenth of equity, from 0-10% up. All zero without any weight.
+    pub fn histogram(&self) -> [f64; 10] {
+        let mut buckets = [0.0; 10];
+        let total = self.total_weight();
+        if total == 0.0 {
+            return buckets;
+        }
+        for (equity, weight) in &self.equities {
+            buckets[((equity * 10.0) as usize).min(9)] += weight / total;
+        }
+        buckets
+    }
+
+    /// Equity at each share of the weight, from the lowest equity up. Shares are zero without
+    /// any weight.
+    pub fn curve(&self) -> Vec<(f64, f64)> {
+        let total = self.total_weight();
+        let mut covered = 0.0;
+        self.equities
+            .iter()
+            .map(|(equity, weight)| {
+                if total > 0.0 {
+                    covered += weight / total;
+                }
+                (covered, *equity)
+            })
+            .collect()
+    }
+
+    fn total_weight(&self) -> f64 {
+        self.equities.iter().map(|(_, weight)| weight).sum()
+    }
+
+    /// Share of the weight with an equity passing `filter`, or zero without any weight
+    fn share(&self, filter: impl Fn(f64) -> bool) -> f64 {
+        let total = self.total_weight();
+        if total == 0.0 {
+            return 0.0;
+        }
+        self.equities
+            .iter()
+            .filter(|(equity, _)| filter(*equity))
+            .fold(0.0, |share, (_, weight)| share + weight)
+            / total
+    }
+
+    /// Share of the weight with at least three quarters of the pot
+    pub fn crushing(&self) -> f64 {
+        self.share(|equity| equity >= CRUSHING)
+    }
+
+    /// Share of the weight close to even odds
+    pub fn flipping(&self) -> f64 {
+        self.share(|equity| FLIP.contains(&equity))
+    }
+
+    /// Share of the weight with at most a quarter of the pot
+    pub fn crushed(&self) -> f64 {
+        self.share(|equity| equity <= CRUSHED)
+    }
+}
+
+impl std::fmt::Display for EquityDistribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "crushing {:.0}%, flipping {:.0}%, crushed {:.0}%",
+            self.crushing() * 100.0,
+            self.flipping() * 100.0,
+            self.crushed() * 100.0
+        )
+    }
+}
+
+/// Cards of a hand as bits of `Card::index`
+fn mask(hand: [Card; 2]) -> u64 {
+    hand.iter().fold(0, |mask, card| mask | 1 << card.index())
+}
+
+/// Opponent combinations live on one board, strongest last, with a running total of their
+/// weights and the combinations holding each card
+struct Showdown {
+    opponents: Vec<(Ranking, [Card; 2], f64)>,
+    /// Weight of the opponents before each position, then of all of them
+    weight_before: Vec<f64>,
+    holding: Vec<Vec<usize>>,
+}
+
+impl Showdown {
+    fn new(variant: Variant, board: &[Card], opponents: &[([Card; 2], f64)]) -> Self {
+        let board_mask = board.iter().fold(0u64, |m, c| m | 1 << c.index());
+        let mut opponents = opponents
+            .iter()
+            .filter(|(hand, _)| mask(*hand) & board_mask == 0)
+            .map(|(hand, weight)| (variant.best_rank_for(hand, board), *hand, *weight))
+            .collect::<Vec<_>>();
+        opponents.sort_by_key(|o| o.0);
+        let mut weight_before = vec![0.0];
+        let mut holding = vec![vec![]; 52];
+        for (i, (_, hand, weight)) in opponents.iter().enumerate() {
+            weight_before.push(weight_before[i] + weight);
+            for card in hand {
+                holding[card.index()].push(i);
+            }
+        }
+        Showdown {
+            opponents,
+            weight_before,
+            holding,
+        }
+    }
+
+    /// Pots won against the opponents that don't share a card with `hand` holding `rank`, and
+    /// their weight
+    fn play(&self, hand: [Card; 2], rank: Ranking) -> (f64, f64) {
+        let below = self.opponents.partition_point(|o| o.0 < rank);
+        let up_to = self.opponents.partition_point(|o| o.0 <= rank);
+        let mut won = self.weight_before[below]
+            + (self.weight_before[up_to] - self.weight_before[below]) / 2.0;
+        let mut total = *self.weight_before.last().unwrap();
+        let blocked = self.holding[hand[0].index()].iter().chain(
+            self.holding[hand[1].index()]
+                .iter()
+                .filter(|i| !self.opponents[**i].1.contains(&hand[0])),
+        );
+        for i in blocked {
+            let (opponent_rank, _, weight) = self.opponents[*i];
+            total -= weight;
+            won -= match opponent_rank.cmp(&rank) {
+                std::cmp::Ordering::Less => weight,
+                std::cmp::Ordering::Equal => weight / 2.0,
+                std::cmp::Ordering::Greater => 0.0,
+            };
+        }
+        (won, total)
+    }
+}
+
+impl DeckState {
+    /// Equity of every combination of `range` against the whole of `opponents`, as equity and
+    /// weight, dealing the rest of the board every way. Preflop the matchup table is used
+    /// when it has been calculated, and random boards otherwise. Gives up once `cancel` is
+    /// set.
+    fn combo_equities(
+        &self,
+        range: &Range,
+        opponents: &Range,
+        cancel: &CancelToken,
+        progress: &impl Fn(Progress),
+    ) -> Option<Vec<(f64, f64)>> {
+        let board = self.board.into_iter().collect::<Vec<_>>();
+        let known = board
+            .iter()
+            .copied()
+            .chain(self.dead)
+            .chain(self.variant.missing_cards())
+            .collect::<Vec<_>>();
+        let combos = range
+            .clone()
+            .without(known.clone())
+            .combos()
+            .collect::<Vec<_>>();
+        let opponents = opponents.clone().without(known.clone());
+        if let (Board::PreFlop, Variant::Holdem, Some(matchups), true) = (
+            self.board,
+            self.variant,
+            matchups(),
+            self.dead == CardSet::default(),
+        ) {
+            return Some(
+                combos
+                    .iter()
+                    .filter(|(hand, _)| opponents.clone().without(*hand).combos().next().is_some())
+                    .map(|(hand, weight)| (matchups.hand_equity(*hand, &opponents), *weight))
+                    .collect(),
+            );
+        }
+        let opponents = opponents.combos().collect::<Vec<_>>();
+        let deck = self.variant.deck_without(known);
+        let runouts = if self.board == Board::PreFlop {
+            let rng = &mut rand::rng();
+            (0..PREFLOP_RUNOUTS)
+                .map(|_| deck.choose_multiple(rng, 5).copied().collect())
+                .collect::<Vec<Vec<Card>>>()
+        } else {
+            deck.into_iter().combinations(5 - board.len()).collect()
+        };
+        let steps = STEPS.min(runouts.len()).max(1);
+        let mut results = vec![(0.0, 0.0); combos.len()];
+        for step in 0..steps {
+            if cancel.is_cancelled() {
+                return None;
+            }
+            let step_results = runouts
+                .par_iter()
+                .skip(step)
+                .step_by(steps)
+                .fold(
+                    || vec![(0.0, 0.0); combos.len()],
+                    |mut results, runout| {
+                        let full_board = board.iter().chain(runout).copied().collect::<Vec<_>>();
+                        let showdown = Showdown::new(self.variant, &full_board, &opponents);
+                        for ((hand, _), result) in combos.iter().zip(results.iter_mut()) {
+                            if hand.iter().any(|c| runout.contains(c)) {
+                                continue;
+                            }
+                            let rank = self.variant.best_rank_for(hand, &full_board);
+                            let (won, total) = showdown.play(*hand, rank);
+                            *result = (result.0 + won, result.1 + total);
+                        }
+                        results
+                    },
+                )
+                .reduce(
+                    || vec![(0.0, 0.0); combos.len()],
+                    |a, b| {
+                        a.iter()
+                            .zip(&b)
+                            .map(|(a, b)| (a.0 + b.0, a.1 + b.1))
+                            .collect()
+                    },
+                );
+            for (result, step_result) in results.iter_mut().zip(step_results) {
+                *result = (result.0 + step_result.0, result.1 + step_result.1);
+            }
+            let (won, total) = results.iter().zip(&combos).fold(
+                (0.0, 0.0),
+                |(won, total), (result, (_, weight))| {
+                    (won + result.0 * weight, total + result.1 * weight)
+                },
+            );
+            progress(Progress {
+                done: (step + 1) as f64 / steps as f64,
+                strength: won / total,
+            });
+        }
+        Some(
+            results
+                .iter()
+                .zip(&combos)
+                .filter(|(result, _)| result.1 > 0.0)
+                .map(|(result, (_, weight))| (result.0 / result.1, *weight))
+                .collect(),
+        )
+    }
+
+    /// The hold'em hand's equity against each combination of `range`, weighted by the
+    /// combination
+    pub fn equity_distribution(
+        &self,
+        range: &Range,
+        cancel: &CancelToken,
+        progress: &impl Fn(Progress),
+    ) -> Option<EquityDistribution> {
+        let hand = Range::from(self.hand.pair()?);
+        let equities = self.combo_equities(range, &hand, cancel, progress)?;
+        Some(EquityDistribution::new(
+            equities
+                .into_iter()
+                .map(|(equity, weight)| (1.0 - equity, weight)),
+        ))
+    }
+
+    /// Equity of each combination of `range` against the whole of `opponents`, weighted by
+    /// the combination
+    pub fn range_distribution(
+        &self,
+        range: &Range,
+        opponents: &Range,
+        cancel: &CancelToken,
+        progress: &impl Fn(Progress),
+    ) -> Option<EquityDistribution> {
+        let equities = self.combo_equities(range, opponents, cancel, progress)?;
+        Some(EquityDistribution::new(equities))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shares of the weight by equity, and zeros without any weight
+    #[test]
+    fn shares() {
+        let distribution =
+            EquityDistribution::new([(0.9, 1.0), (0.1, 2.0), (0.5, 1.0), (0.95, 0.0)]);
+        assert_eq!(distribution.equities[0], (0.1, 2.0));
+        let histogram = distribution.histogram();
+        assert_eq!(
+            (histogram[1], histogram[5], histogram[9]),
+            (0.5, 0.25, 0.25)
+        );
+        assert_eq!(histogram.iter().sum::<f64>(), 1.0);
+        assert_eq!(
+            distribution.curve(),
+            [(0.5, 0.1), (0.75, 0.5), (1.0, 0.9), (1.0, 0.95)]
+        );
+        assert_eq!(
+            (
+                distribution.crushing(),
+                distribution.flipping(),
+                distribution.crushed()
+            ),
+            (0.25, 0.25, 0.5)
+        );
+
+        let empty = EquityDistribution::new([(0.5, 0.0)]);
+        assert_eq!(empty.histogram(), [0.0; 10]);
+        assert_eq!(empty.curve(), [(0.0, 0.5)]);
+        assert_eq!(empty.to_string(), "crushing 0%, flipping 0%, crushed 0%");
+    }
+}
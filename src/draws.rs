@@ -0,0 +1,99 @@
+use crate::cards::*;
+
+/// Draws to a flush or straight that use at least one hole card
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Draws {
+    pub flush_draw: bool,
+    pub open_ended: bool,
+    pub gutshot: bool,
+    /// Number of distinct faces that complete a straight
+    straight_faces: usize,
+    /// Cards left in the deck that complete a flush draw
+    flush_outs: usize,
+}
+
+impl Draws {
+    pub fn find(variant: Variant, hand: [Card; 2], board: &[Card]) -> Self {
+        let mut this = Self {
+            flush_outs: variant.faces().len() - 4,
+            ..Self::default()
+        };
+        // No more cards to come
+        if board.len() >= 5 || board.len() < 3 {
+            return this;
+        }
+        let cards = hand.iter().chain(board).copied().collect::<Vec<_>>();
+
+        for suite in Suite::ALL {
+            let count = cards.iter().filter(|c| c.1 == suite).count();
+            if count == 4 && hand.iter().any(|c| c.1 == suite) {
+                this.flush_draw = true;
+            }
+        }
+
+        // Bit `n` is set when face value `n` is present, aces count both as 14 and just below
+        // the lowest face of the deck
+        let wheel_ace = variant.faces()[0].high() - 1;
+        let mask = |cards: &[Card]| {
+            cards.iter().fold(0u16, |mask, c| {
+                let low = if c.0 == Face::Ace {
+                    wheel_ace
+                } else {
+                    c.0.high()
+                };
+                mask | 1 << low | 1 << c.0.high()
+            })
+        };
+        let all = mask(&cards);
+        let mine = mask(&hand);
+        let window = |low: usize| 0b11111u16 << low;
+        let windows = wheel_ace..=10;
+        if windows.clone().any(|low| all & window(low) == window(low)) {
+            // Already a straight
+            return this;
+        }
+        let mut completing = 0u16;
+        for low in windows {
+            let present = all & window(low);
+            if present.count_ones() == 4 && present & mine != 0 {
+                completing |= window(low) & !present;
+            }
+        }
+        this.straight_faces = completing.count_ones() as usize;
+        this.open_ended = this.straight_faces >= 2;
+        this.gutshot = this.straight_faces == 1;
+        this
+    }
+
+    /// Cards left in the deck that complete a draw
+    pub fn outs(&self) -> usize {
+        let straight = 4 * self.straight_faces;
+        if self.flush_draw {
+            // Straight cards of the flush suit are already counted
+            self.flush_outs + straight - self.straight_faces
+        } else {
+            straight
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The ace completes a straight below the lowest face of the deck
+    #[test]
+    fn short_deck_wheel() {
+        let (hand, board) = (cards!("Ah", "7d"), cards!("8c", "9s", "Kd"));
+        let holdem = Draws::find(Variant::Holdem, hand, &board);
+        assert!(!holdem.gutshot && holdem.outs() == 0);
+        let short = Draws::find(Variant::ShortDeck, hand, &board);
+        assert!(short.gutshot && short.outs() == 4);
+        let made = Draws::find(Variant::ShortDeck, hand, &cards!("8c", "9s", "6d"));
+        assert_eq!(made, Draws::find(Variant::ShortDeck, hand, &[]));
+
+        let flush = cards!("Kh", "8h", "7h");
+        assert_eq!(Draws::find(Variant::Holdem, hand, &flush).outs(), 9);
+        assert_eq!(Draws::find(Variant::ShortDeck, hand, &flush).outs(), 5);
+    }
+}
@@ -1,7 +1,23 @@
+use std::sync::{
+    Arc, LazyLock, Mutex, Once, OnceLock,
+    atomic::{AtomicBool, Ordering},
+};
+
 use itertools::Itertools;
-use rayon::iter::{IntoParallelIterator, ParallelBridge, ParallelIterator};
+use rand::{distr::Distribution, seq::IndexedRandom};
+use rayon::prelude::*;
 
-use crate::{cards::*, preflop::HandChart, rank::*, state::*};
+use crate::{
+    cache::EquityCache,
+    cards::*,
+    iso::*,
+    matchups::Matchups,
+    preflop::*,
+    range::{OmahaRange, OpponentRange, Range, omaha_score},
+    rank::*,
+    state::*,
+    storage,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, PartialOrd, Ord)]
 pub enum Recommendation {
@@ -39,135 +55,627 @@ impl Recommendation {
 }
 
 /// Convert wins/ties/losses to probability of victory
-fn results_to_strength(wins: usize, ties: usize, losses: usize) -> f64 {
-    let (wins, ties, losses) = (wins as f64, ties as f64, losses as f64);
+fn results_to_strength(wins: f64, ties: f64, losses: f64) -> f64 {
     (wins + ties / 2.0) / (wins + ties + losses)
 }
 
+/// Add a weighted showdown result to a wins/ties/losses tally
+fn tally(
+    (wins, ties, losses): (f64, f64, f64),
+    this_rank: Ranking,
+    opponent_rank: Ranking,
+    weight: f64,
+) -> (f64, f64, f64) {
+    match this_rank.cmp(&opponent_rank) {
+        std::cmp::Ordering::Greater => (wins + weight, ties, losses),
+        std::cmp::Ordering::Equal => (wins, ties + weight, losses),
+        std::cmp::Ordering::Less => (wins, ties, losses + weight),
+    }
+}
+
+/// Spot in which equity was calculated, the same for spots that only differ by exchanging suits
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpotKey {
+    pub variant: Variant,
+    /// Hole, board and dead cards
+    pub rounds: Vec<usize>,
+    /// Index of the deal among deals of `rounds` cards
+    pub index: usize,
+    /// Fingerprint of the opponent range after exchanging suits the same way
+    pub range: u64,
+}
+
+/// Equity of spots calculated in this and earlier sessions
+static EQUITY_CACHE: LazyLock<Mutex<EquityCache>> = LazyLock::new(|| {
+    let path = storage::data_path("equity.tsv");
+    Mutex::new(EquityCache::load(&path).unwrap_or_else(|e| {
+        eprintln!("{}: {e}", path.display());
+        EquityCache::new(&path)
+    }))
+});
+
+/// Preflop all-in equities of every class against every other, once they have been loaded or
+/// calculated
+static MATCHUPS: OnceLock<Matchups> = OnceLock::new();
+
+/// The preflop matchup table, once it has been saved in an earlier session or calculated in
+/// this one
+pub fn matchups() -> Option<&'static Matchups> {
+    static LOAD: Once = Once::new();
+    LOAD.call_once(|| {
+        let path = storage::data_path(MATCHUPS_FILE);
+        match Matchups::load(&path) {
+            Ok(matchups) => {
+                let _ = MATCHUPS.set(matchups);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+            Err(e) => eprintln!("{}: {e}", path.display()),
+        }
+    });
+    MATCHUPS.get()
+}
+
+/// The preflop matchup table, calculating and saving it first when no valid table has been
+/// saved, which takes about an hour on a single core. Calls `progress` with the number of
+/// boards done and the total while calculating.
+pub fn matchups_or_calculate(progress: impl FnMut(usize, usize)) -> &'static Matchups {
+    if let Some(matchups) = matchups() {
+        return matchups;
+    }
+    let matchups = Matchups::calculate(progress);
+    let path = storage::data_path(MATCHUPS_FILE);
+    if let Err(e) = matchups.save(&path) {
+        eprintln!("{}: {e}", path.display());
+    }
+    MATCHUPS.get_or_init(|| matchups)
+}
+
+/// File in the data directory holding the preflop matchup equities
+pub const MATCHUPS_FILE: &str = "matchups.tsv";
+
+/// Random opponent hands and boards used for Omaha equity
+pub const OMAHA_SAMPLES: usize = 20000;
+
+/// Random boards dealt for preflop all-in equity when the matchup table can't be used
+pub const PREFLOP_RUNOUTS: usize = 1000;
+
+/// Parts a long equity calculation is split into, reporting progress after each
+pub const STEPS: usize = 20;
+
+/// Lets an equity calculation running on another thread be stopped
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// How far an equity calculation has got
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Progress {
+    /// Share of the work done, from 0 to 1
+    pub done: f64,
+    /// Strength estimated from the work done so far
+    pub strength: f64,
+}
+
+/// Tally showdowns over `items` in steps, each taking an evenly spread share of them so that
+/// the strength reported after every step is a fair estimate. Gives up once `cancel` is set.
+fn tally_in_steps<T: Sync>(
+    items: &[T],
+    showdowns: impl Fn((f64, f64, f64), &T) -> (f64, f64, f64) + Sync,
+    cancel: &CancelToken,
+    progress: &impl Fn(Progress),
+) -> Option<(f64, f64, f64)> {
+    let steps = STEPS.min(items.len()).max(1);
+    let mut results = (0.0, 0.0, 0.0);
+    for step in 0..steps {
+        if cancel.is_cancelled() {
+            return None;
+        }
+        let (wins, ties, losses) = items
+            .par_iter()
+            .skip(step)
+            .step_by(steps)
+            .fold(|| (0.0, 0.0, 0.0), &showdowns)
+            .reduce(
+                || (0.0, 0.0, 0.0),
+                |(w, t, l), (w2, t2, l2)| (w + w2, t + t2, l + l2),
+            );
+        results = (results.0 + wins, results.1 + ties, results.2 + losses);
+        progress(Progress {
+            done: (step + 1) as f64 / steps as f64,
+            strength: results_to_strength(results.0, results.1, results.2),
+        });
+    }
+    Some(results)
+}
+
+/// Samples to take in `step` of `STEPS` when `samples` are taken in all
+fn samples_in_step(step: usize, samples: usize) -> std::ops::Range<usize> {
+    step * samples / STEPS..(step + 1) * samples / STEPS
+}
+
+/// Expected share of a split pot, from the high and low halves
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct HiLoEquity {
+    /// Share of the pot won with the high hand, including the whole pot when no low qualifies
+    pub high: f64,
+    /// Share of the pot won with the low hand
+    pub low: f64,
+    /// Chance of winning the whole pot
+    pub scoop: f64,
+}
+
+impl HiLoEquity {
+    pub fn total(&self) -> f64 {
+        self.high + self.low
+    }
+
+    /// Add the high and low shares won at one showdown
+    pub fn record(&mut self, (high, low): (f64, f64)) {
+        self.high += high;
+        self.low += low;
+        self.scoop += if high + low == 1.0 { 1.0 } else { 0.0 };
+    }
+
+    /// Average over `samples` recorded showdowns
+    pub fn averaged(self, samples: usize) -> Self {
+        let samples = samples.max(1) as f64;
+        HiLoEquity {
+            high: self.high / samples,
+            low: self.low / samples,
+            scoop: self.scoop / samples,
+        }
+    }
+}
+
+impl std::fmt::Display for HiLoEquity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "high {:.1}%, low {:.1}%, scoop {:.1}%",
+            self.high * 100.0,
+            self.low * 100.0,
+            self.scoop * 100.0
+        )
+    }
+}
+
 impl DeckState {
+    /// Range of hands the opponent is expected to hold before any action is observed
+    pub fn opponent_range(&self) -> Range {
+        if self.variance == Variance::Random {
+            Range::full()
+        } else {
+            Range::from(HandChart::opponent_expectation())
+        }
+    }
+
     pub fn strength(&self) -> f64 {
-        match self.board {
-            Board::PreFlop => self.preflop_strength(),
-            Board::Flop(_) => self.strength_after_n_deals(2),
-            Board::Turn(_) => self.strength_after_n_deals(1),
-            Board::River(_) => self.board_strength(),
+        self.strength_against(&self.opponent_range())
+    }
+
+    /// Strength of the hand against a weighted opponent range. Omaha hands are instead played
+    /// against every possible hand, as ranges only hold two card combinations.
+    pub fn strength_against(&self, range: &Range) -> f64 {
+        let range = OpponentRange::Combos(range.clone());
+        self.cancellable_strength_against(&range, &CancelToken::default(), &|_| ())
+            .unwrap()
+    }
+
+    /// Strength of the hand against an opponent range like `strength_against`, calling
+    /// `progress` with the estimate so far as the calculation goes. Omaha hands are played
+    /// against an Omaha range, or every hand when given two card combinations, and other hands
+    /// against every combination when given an Omaha range. Gives up once `cancel` is set.
+    pub fn cancellable_strength_against(
+        &self,
+        range: &OpponentRange,
+        cancel: &CancelToken,
+        progress: &impl Fn(Progress),
+    ) -> Option<f64> {
+        let range = match (self.variant.omaha(), range) {
+            (true, OpponentRange::Omaha(range)) => {
+                return self.sampled_omaha_strength(range, OMAHA_SAMPLES, cancel, progress);
+            }
+            (true, OpponentRange::Combos(_)) => {
+                return self.sampled_omaha_strength(
+                    &OmahaRange::default(),
+                    OMAHA_SAMPLES,
+                    cancel,
+                    progress,
+                );
+            }
+            (false, OpponentRange::Combos(range)) => range.clone(),
+            (false, OpponentRange::Omaha(_)) => Range::full(),
+        };
+        let range = range.without(self.known_cards());
+        let deals = match self.board {
+            // Quick to look up or estimate, and kept out of the cache so that the matchup table
+            // is used once it has been calculated
+            Board::PreFlop => return self.preflop_strength(&range, cancel, progress),
+            Board::Flop(_) => 2,
+            Board::Turn(_) => 1,
+            Board::River(_) => 0,
+        };
+        let key = self.spot_key(&range);
+        if let Some(strength) = EQUITY_CACHE.lock().unwrap().get(&key) {
+            return Some(strength);
         }
+        let strength = self.strength_after_n_deals(&range, deals, cancel, progress)?;
+        // A cache that can't be written only costs time in later sessions
+        let _ = EQUITY_CACHE.lock().unwrap().insert(key, strength);
+        Some(strength)
     }
 
-    /// Strength of the hand given only the cards on the board
-    fn board_strength(&self) -> f64 {
-        let opponent_range = if self.variance == Variance::Random {
-            HandChart::filled_with(Recommendation::Call)
-        } else {
-            HandChart::opponent_expectation()
+    /// Key shared by every spot that only differs from this one by exchanging suits
+    fn spot_key(&self, range: &Range) -> SpotKey {
+        let board = self.board.into_iter().collect::<Vec<_>>();
+        let dead = self.dead.into_iter().collect::<Vec<_>>();
+        let deal = [self.hand.cards(), &board, &dead];
+        let permutation = suit_permutation(&deal);
+        SpotKey {
+            variant: self.variant,
+            rounds: deal.iter().map(|r| r.len()).collect(),
+            index: indexer(&deal.map(|r| r.len())).index(&deal),
+            range: range.permuted(permutation).fingerprint(),
+        }
+    }
+
+    /// Deal the rest of the board around the hand and `opponent_hand`
+    fn sample_runout(&self, rng: &mut impl rand::Rng, opponent_hand: &[Card]) -> Vec<Card> {
+        let board = self.board.into_iter().collect::<Vec<_>>();
+        let deck = self
+            .variant
+            .deck_without(self.known_cards().chain(opponent_hand.iter().copied()));
+        deck.choose_multiple(rng, 5 - board.len())
+            .chain(&board)
+            .copied()
+            .collect()
+    }
+
+    /// Deal the rest of the board and compare the hand with `opponent_hand`
+    fn sample_showdown(
+        &self,
+        rng: &mut impl rand::Rng,
+        opponent_hand: &[Card],
+        results: (f64, f64, f64),
+    ) -> (f64, f64, f64) {
+        let runout = self.sample_runout(rng, opponent_hand);
+        let this_rank = self.variant.best_rank_for(self.hand.cards(), &runout);
+        let opponent_rank = self.variant.best_rank_for(opponent_hand, &runout);
+        tally(results, this_rank, opponent_rank, 1.0)
+    }
+
+    /// Estimate strength against a weighted range from `samples` random opponent hands and
+    /// boards, for when an exact calculation would take too long. An empty range gives even odds.
+    pub fn sampled_strength_against(&self, range: &Range, samples: usize) -> f64 {
+        let combos = range
+            .clone()
+            .without(self.known_cards())
+            .combos()
+            .collect::<Vec<_>>();
+        let Ok(weights) = rand::distr::weighted::WeightedIndex::new(combos.iter().map(|c| c.1))
+        else {
+            return 0.5;
         };
+        let rng = &mut rand::rng();
+        let (wins, ties, losses) = (0..samples).fold((0.0, 0.0, 0.0), |results, _| {
+            let (opponent_hand, _) = combos[weights.sample(rng)];
+            self.sample_showdown(rng, &opponent_hand, results)
+        });
+        results_to_strength(wins, ties, losses)
+    }
+
+    /// Deal an Omaha hand with as many hole cards as ours from the top of `range`, where
+    /// `threshold` is the range's lowest score among hands from `deck`. Gives up once `cancel`
+    /// is set.
+    fn sample_omaha_hand(
+        &self,
+        rng: &mut impl rand::Rng,
+        deck: &[Card],
+        threshold: f64,
+        cancel: &CancelToken,
+    ) -> Option<Vec<Card>> {
+        while !cancel.is_cancelled() {
+            let hand = deck
+                .choose_multiple(rng, self.hand.cards().len())
+                .copied()
+                .collect::<Vec<_>>();
+            if omaha_score(&hand) >= threshold {
+                return Some(hand);
+            }
+        }
+        None
+    }
+
+    /// Estimate strength against Omaha hands with as many hole cards as ours, dealt at random
+    /// from `range`. Split-pot games count the expected share of the pot. Calls `progress` with
+    /// the estimate so far as the samples are taken, and gives up once `cancel` is set.
+    pub fn sampled_omaha_strength(
+        &self,
+        range: &OmahaRange,
+        samples: usize,
+        cancel: &CancelToken,
+        progress: &impl Fn(Progress),
+    ) -> Option<f64> {
+        if self.variant.hi_lo() {
+            return self
+                .sampled_hi_lo_equity(range, samples, cancel, progress)
+                .map(|equity| equity.total());
+        }
+        let rng = &mut rand::rng();
+        let deck = self.variant.deck_without(self.known_cards());
+        let threshold = range.threshold(&deck, self.hand.cards().len());
+        let mut results = (0.0, 0.0, 0.0);
+        for step in 0..STEPS {
+            if cancel.is_cancelled() {
+                return None;
+            }
+            for _ in samples_in_step(step, samples) {
+                let opponent_hand = self.sample_omaha_hand(rng, &deck, threshold, cancel)?;
+                results = self.sample_showdown(rng, &opponent_hand, results);
+            }
+            progress(Progress {
+                done: (step + 1) as f64 / STEPS as f64,
+                strength: results_to_strength(results.0, results.1, results.2),
+            });
+        }
+        Some(results_to_strength(results.0, results.1, results.2))
+    }
+
+    /// Estimate the high share, low share and scoop chance against Omaha hands dealt at random
+    /// from `range`, reporting the total share so far like `sampled_omaha_strength`
+    pub fn sampled_hi_lo_equity(
+        &self,
+        range: &OmahaRange,
+        samples: usize,
+        cancel: &CancelToken,
+        progress: &impl Fn(Progress),
+    ) -> Option<HiLoEquity> {
+        let rng = &mut rand::rng();
+        let deck = self.variant.deck_without(self.known_cards());
+        let threshold = range.threshold(&deck, self.hand.cards().len());
+        let mut equity = HiLoEquity::default();
+        for step in 0..STEPS {
+            if cancel.is_cancelled() {
+                return None;
+            }
+            for _ in samples_in_step(step, samples) {
+                let opponent_hand = self.sample_omaha_hand(rng, &deck, threshold, cancel)?;
+                let runout = self.sample_runout(rng, &opponent_hand);
+                let hands = [self.hand.cards(), &opponent_hand];
+                let highs = hands.map(|hand| Some(self.variant.best_rank_for(hand, &runout)));
+                let lows = hands.map(|hand| self.variant.best_low_for(hand, &runout));
+                equity.record(pot_shares(&highs, &lows)[0]);
+            }
+            progress(Progress {
+                done: (step + 1) as f64 / STEPS as f64,
+                strength: equity.averaged(samples_in_step(step, samples).end).total(),
+            });
+        }
+        Some(equity.averaged(samples))
+    }
+
+    /// Strength of the hand given only the cards on the board
+    fn board_strength(&self, range: &Range) -> f64 {
         let this_rank = self.current_rank();
-        let (wins, ties, losses) = deck_without(self.clone())
-            .into_iter()
-            .combinations(2)
-            .filter(|hand| opponent_range.filter_hand([hand[0], hand[1]]))
-            .map(|opponent_hand| {
-                Ranking::from(best_hand_in(
-                    opponent_hand.into_iter().chain(self.board.into_iter()),
-                ))
-            })
-            .fold(
-                (0, 0, 0),
-                |(wins, ties, losses), opponent_rank| match this_rank.cmp(&opponent_rank) {
-                    std::cmp::Ordering::Greater => (wins + 1, ties, losses),
-                    std::cmp::Ordering::Equal => (wins, ties + 1, losses),
-                    std::cmp::Ordering::Less => (wins, ties, losses + 1),
-                },
-            );
+        let board = self.board.into_iter().collect::<Vec<_>>();
+        let (wins, ties, losses) =
+            range
+                .combos()
+                .fold((0.0, 0.0, 0.0), |results, (opponent_hand, weight)| {
+                    let opponent_rank = self.variant.best_rank_for(&opponent_hand, &board);
+                    tally(results, this_rank, opponent_rank, weight)
+                });
         results_to_strength(wins, ties, losses)
     }
 
     /// Strength of the hand after `n` deals
-    fn strength_after_n_deals(&self, n: usize) -> f64 {
+    fn strength_after_n_deals(
+        &self,
+        range: &Range,
+        n: usize,
+        cancel: &CancelToken,
+        progress: &impl Fn(Progress),
+    ) -> Option<f64> {
         if n == 0 {
-            return self.board_strength();
+            return Some(self.board_strength(range));
         }
-        let opponent_range = if self.variance == Variance::Random {
-            HandChart::filled_with(Recommendation::Call)
-        } else {
-            HandChart::opponent_expectation()
-        };
-        //let (mut wins, mut ties, mut losses) = (0, 0, 0);
-        let (wins, ties, losses) = deck_without(self.clone())
-            .into_iter()
-            .combinations(2)
-            .filter(|hand| opponent_range.filter_hand([hand[0], hand[1]]))
-            .par_bridge()
-            .fold(
-                || (0, 0, 0),
-                |(mut wins, mut ties, mut losses), opponent_hand| {
-                    //
-                    for flop in deck_without(self.clone().into_iter().chain(opponent_hand.clone()))
-                        .into_iter()
-                        .combinations(n)
-                    {
-                        let this_rank =
-                            Ranking::from(best_hand_in(self.into_iter().chain(flop.clone())));
-                        let opponent_rank = Ranking::from(best_hand_in(
-                            self.board
-                                .into_iter()
-                                .chain(flop)
-                                .chain(opponent_hand.clone()),
-                        ));
-                        match this_rank.cmp(&opponent_rank) {
-                            std::cmp::Ordering::Greater => wins += 1,
-                            std::cmp::Ordering::Equal => ties += 1,
-                            std::cmp::Ordering::Less => losses += 1,
-                        }
-                    }
-                    (wins, ties, losses)
-                },
-            )
-            .collect::<Vec<_>>()
-            .into_iter()
-            .fold((0, 0, 0), |(w, t, l), (w2, t2, l2)| {
-                (w + w2, t + t2, l + l2)
+        let combos = range.combos().collect::<Vec<_>>();
+        let (wins, ties, losses) = tally_in_steps(
+            &combos,
+            |mut results, &(opponent_hand, weight)| {
+                for flop in self
+                    .variant
+                    .deck_without(self.known_cards().chain(opponent_hand))
+                    .into_iter()
+                    .combinations(n)
+                {
+                    let board = self.board.into_iter().chain(flop).collect::<Vec<_>>();
+                    let this_rank = self.variant.best_rank_for(self.hand.cards(), &board);
+                    let opponent_rank = self.variant.best_rank_for(&opponent_hand, &board);
+                    results = tally(results, this_rank, opponent_rank, weight);
+                }
+                results
+            },
+            cancel,
+            progress,
+        )?;
+        Some(results_to_strength(wins, ties, losses))
+    }
+
+    /// All-in equity of the hand before the flop, as the equity distribution counts it: from
+    /// the matchup table when it has been calculated and no cards are dead, and otherwise from
+    /// random boards
+    fn preflop_strength(
+        &self,
+        range: &Range,
+        cancel: &CancelToken,
+        progress: &impl Fn(Progress),
+    ) -> Option<f64> {
+        if let (Variant::Holdem, Some(hand), Some(matchups), true) = (
+            self.variant,
+            self.hand.pair(),
+            matchups(),
+            self.dead == CardSet::default(),
+        ) {
+            let strength = matchups.hand_equity(hand, range);
+            progress(Progress {
+                done: 1.0,
+                strength,
             });
+            return Some(strength);
+        }
+        let combos = range.combos().collect::<Vec<_>>();
+        let deck = self.variant.deck_without(self.known_cards());
+        let rng = &mut rand::rng();
+        let runouts = (0..PREFLOP_RUNOUTS)
+            .map(|_| deck.choose_multiple(rng, 5).copied().collect())
+            .collect::<Vec<Vec<Card>>>();
+        let (wins, ties, losses) = tally_in_steps(
+            &runouts,
+            |mut results, runout| {
+                let this_rank = self.variant.best_rank_for(self.hand.cards(), runout);
+                for (opponent_hand, weight) in &combos {
+                    if opponent_hand.iter().any(|c| runout.contains(c)) {
+                        continue;
+                    }
+                    let opponent_rank = self.variant.best_rank_for(opponent_hand, runout);
+                    results = tally(results, this_rank, opponent_rank, *weight);
+                }
+                results
+            },
+            cancel,
+            progress,
+        )?;
+        Some(results_to_strength(wins, ties, losses))
+    }
+}
 
-        results_to_strength(wins, ties, losses)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn river(hand: [Card; 2], dead: &[Card]) -> DeckState {
+        DeckState {
+            board: Board::from_cards(&cards!("Ks", "7h", "2c", "3d", "9s")).unwrap(),
+            hand: HoleCards::from(hand),
+            variance: Variance::Random,
+            dead: dead.iter().copied().collect(),
+            variant: Variant::Holdem,
+        }
     }
 
-    // Optimized strength calculation considering only unique opening hands
-    fn preflop_strength(&self) -> f64 {
-        //let opponent_range = HandChart::opponent_expectation();
-        let opponent_range = if self.variance == Variance::Random {
-            HandChart::filled_with(Recommendation::Call)
-        } else {
-            HandChart::opponent_expectation()
+    /// Dead cards can't be held by the opponent
+    #[test]
+    fn dead_cards() {
+        let aces = cards!("As", "Ad");
+        let kings = cards!("Kh", "Kd", "Kc");
+        let live = river(aces, &[]).strength();
+        let dead = river(aces, &kings).strength();
+        assert!(dead > live);
+        // The range leaves out every combination holding a dead card
+        let range = Range::full().without(river(aces, &kings).known_cards());
+        assert!(
+            range
+                .combos()
+                .all(|(hand, _)| !hand.iter().any(|c| kings.contains(c)))
+        );
+    }
+
+    /// Without the matchup table, preflop strength is all-in equity over the whole board,
+    /// where ace-king is close to even against a small pair rather than far behind on the flop
+    #[test]
+    fn preflop_all_in_equity() {
+        let state = DeckState {
+            board: Board::PreFlop,
+            ..river(cards!("Ah", "Kh"), &cards!("9d"))
         };
-        let (wins, ties, losses) = unique_open_hands()
-            .into_par_iter()
-            .filter(|hand| opponent_range.filter_hand(*hand))
-            .fold(
-                || (0, 0, 0),
-                |(mut wins, mut ties, mut losses), opponent_hand| {
-                    for flop in deck_without(opponent_hand.into_iter().chain(self.hand))
-                        .into_iter()
-                        .combinations(3)
-                    {
-                        let this_rank =
-                            Ranking::from(best_hand_in(flop.clone().into_iter().chain(self.hand)));
-                        let opponent_rank =
-                            Ranking::from(best_hand_in(flop.into_iter().chain(opponent_hand)));
-                        match this_rank.cmp(&opponent_rank) {
-                            std::cmp::Ordering::Greater => wins += 1,
-                            std::cmp::Ordering::Equal => ties += 1,
-                            std::cmp::Ordering::Less => losses += 1,
-                        }
-                    }
-                    (wins, ties, losses)
-                },
-            )
-            .collect::<Vec<_>>()
-            .into_iter()
-            .fold((0, 0, 0), |(w, t, l), (w2, t2, l2)| {
-                (w + w2, t + t2, l + l2)
+        let range = OpponentRange::Combos(Range::from(cards!("2s", "2c")));
+        let strength = state
+            .cancellable_strength_against(&range, &CancelToken::default(), &|_| ())
+            .unwrap();
+        assert!((strength - 0.5).abs() < 0.06, "{strength}");
+    }
+
+    /// A cancelled calculation gives up, and one left to run reports its progress up to the
+    /// final strength
+    #[test]
+    fn cancellation() {
+        let flop = DeckState {
+            board: Board::from_cards(&cards!("Ks", "7h", "2c")).unwrap(),
+            ..river(cards!("As", "Ad"), &[])
+        };
+        let mut range = Range::from(cards!("7s", "6s"));
+        for combo in [cards!("7d", "6d"), cards!("7c", "6c")] {
+            range.set_weight(combo, 1.0);
+        }
+        let range = OpponentRange::Combos(range);
+        let cancel = CancelToken::default();
+        cancel.cancel();
+        assert_eq!(
+            flop.cancellable_strength_against(&range, &cancel, &|_| ()),
+            None
+        );
+        let omaha = DeckState {
+            hand: HoleCards::new(&cards!("As", "Ad", "Kh", "Qh")).unwrap(),
+            variant: Variant::Omaha,
+            ..flop
+        };
+        assert_eq!(
+            omaha.cancellable_strength_against(&range, &cancel, &|_| ()),
+            None
+        );
+
+        let reports = Mutex::new(vec![]);
+        let strength = flop
+            .cancellable_strength_against(&range, &CancelToken::default(), &|progress| {
+                reports.lock().unwrap().push(progress)
+            })
+            .unwrap();
+        let reports = reports.into_inner().unwrap();
+        assert_eq!(reports.len(), 3);
+        assert!(reports.windows(2).all(|w| w[0].done < w[1].done));
+        assert_eq!(
+            reports.last(),
+            Some(&Progress {
+                done: 1.0,
+                strength
+            })
+        );
+    }
+
+    /// Omaha hands are played against the strongest hands of the range given, dealt from the
+    /// cards left even when few of them make strong hands
+    #[test]
+    fn omaha_range() {
+        let omaha = DeckState {
+            board: Board::from_cards(&cards!("As", "Ah", "Kd")).unwrap(),
+            hand: HoleCards::new(&cards!("9c", "8c", "6d", "5h")).unwrap(),
+            variant: Variant::Omaha,
+            dead: cards!("Ad", "Ac", "Ks", "Kh", "Kc", "Qs", "Qh", "Qd", "Qc")
+                .into_iter()
+                .collect(),
+            ..river(cards!("As", "Ad"), &[])
+        };
+        // The estimate after the first step of samples is enough to tell the ranges apart
+        let strength = |top| {
+            let range = OpponentRange::Omaha(OmahaRange { top });
+            let cancel = CancelToken::default();
+            let first = Mutex::new(None);
+            omaha.cancellable_strength_against(&range, &cancel, &|progress| {
+                first.lock().unwrap().get_or_insert(progress.strength);
+                cancel.cancel();
             });
-        results_to_strength(wins, ties, losses)
+            first.into_inner().unwrap().unwrap()
+        };
+        assert!(strength(0.05) < strength(1.0) - 0.1);
     }
 }
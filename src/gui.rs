@@ -18,3 +18,146 @@ pub fn text_entry(ui: &mut egui::Ui, label: impl Into<egui::WidgetText>, output:
 pub fn emphasized(str: impl std::fmt::Display, color: Color32) -> RichText {
     RichText::new(format!("{str}")).color(color).underline()
 }
+
+/// Paint a grid of starting hand classes, shaded by how much of each class is in a range
+pub fn range_heatmap(ui: &mut egui::Ui, weights: &[[f64; 13]; 13]) {
+    const CELL: f32 = 15.0;
+    let (rect, _) = ui.allocate_exact_size(egui::Vec2::splat(CELL * 13.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    for (x, row) in weights.iter().enumerate() {
+        for (y, weight) in row.iter().enumerate() {
+            let cell = egui::Rect::from_min_size(
+                rect.min + egui::vec2(y as f32 * CELL, x as f32 * CELL),
+                egui::Vec2::splat(CELL),
+            );
+            let color = Color32::from_gray(40).lerp_to_gamma(Color32::DARK_GREEN, *weight as f32);
+            painter.rect_filled(cell.shrink(0.5), 1.0, color);
+            painter.text(
+                cell.center(),
+                egui::Align2::CENTER_CENTER,
+                crate::preflop::OpeningHand::at(x, y),
+                egui::FontId::monospace(4.5),
+                Color32::WHITE,
+            );
+        }
+    }
+}
+
+/// Paint a line through values from 0 to 1, with each point's label beneath it
+pub fn line_chart(ui: &mut egui::Ui, points: &[(String, f64)]) {
+    const WIDTH: f32 = 195.0;
+    const HEIGHT: f32 = 60.0;
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(WIDTH, HEIGHT + 8.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let plot = egui::Rect::from_min_size(rect.min, egui::vec2(WIDTH, HEIGHT));
+    painter.rect_filled(plot, 1.0, Color32::from_gray(40));
+    let positions = points
+        .iter()
+        .enumerate()
+        .map(|(i, (_, value))| {
+            egui::pos2(
+                plot.left() + (i as f32 + 0.5) * WIDTH / points.len() as f32,
+                plot.bottom() - *value as f32 * HEIGHT,
+            )
+        })
+        .collect::<Vec<_>>();
+    painter.line(
+        positions.clone(),
+        egui::Stroke::new(1.0_f32, Color32::ORANGE),
+    );
+    for (position, (label, value)) in positions.into_iter().zip(points) {
+        painter.circle_filled(position, 2.0, Color32::ORANGE);
+        painter.text(
+            position - egui::vec2(0.0, 3.0),
+            egui::Align2::CENTER_BOTTOM,
+            format!("{:.0}%", value * 100.0),
+            egui::FontId::proportional(5.0),
+            Color32::WHITE,
+        );
+        painter.text(
+            egui::pos2(position.x, plot.bottom() + 1.0),
+            egui::Align2::CENTER_TOP,
+            label,
+            egui::FontId::proportional(5.0),
+            Color32::WHITE,
+        );
+    }
+}
+
+/// Paint a bar for each value from 0 to 1 in its color, with even odds marked across,
+/// returning the area painted
+pub fn bar_chart(ui: &mut egui::Ui, bars: &[(f64, Color32)]) -> egui::Rect {
+    const WIDTH: f32 = 195.0;
+    const HEIGHT: f32 = 60.0;
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(WIDTH, HEIGHT), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 1.0, Color32::from_gray(40));
+    let bar_width = WIDTH / bars.len().max(1) as f32;
+    for (i, (value, color)) in bars.iter().enumerate() {
+        let left = rect.left() + i as f32 * bar_width;
+        let bar = egui::Rect::from_min_max(
+            egui::pos2(left, rect.bottom() - *value as f32 * HEIGHT),
+            egui::pos2(left + bar_width, rect.bottom()),
+        );
+        painter.rect_filled(bar.shrink2(egui::vec2(bar_width * 0.1, 0.0)), 0.0, *color);
+    }
+    painter.hline(
+        rect.x_range(),
+        rect.center().y,
+        egui::Stroke::new(0.5_f32, Color32::WHITE),
+    );
+    rect
+}
+
+/// Color of the next cards that make a kind of hand, or don't improve
+pub fn improvement_color(improvement: Option<crate::rank::RankKind>) -> Color32 {
+    use crate::rank::RankKind::*;
+    match improvement {
+        None | Some(HighCard) => Color32::GRAY,
+        Some(OnePair) => Color32::LIGHT_BLUE,
+        Some(TwoPairs) => Color32::LIGHT_GREEN,
+        Some(ThreeKind) => Color32::DARK_GREEN,
+        Some(Straight) => Color32::YELLOW,
+        Some(Flush) => Color32::GOLD,
+        Some(FullHouse) => Color32::ORANGE,
+        Some(FourKind | StraightFlush | RoyalFlush) => Color32::RED,
+    }
+}
+
+/// Paint how equity spreads over a range: a bar for the share of the range in each tenth of
+/// equity, and a curve of the equity reached at each share of the range
+pub fn distribution_chart(
+    ui: &mut egui::Ui,
+    distribution: &crate::distribution::EquityDistribution,
+) {
+    let histogram = distribution.histogram();
+    let tallest = histogram.iter().copied().fold(f64::EPSILON, f64::max);
+    let rect = bar_chart(
+        ui,
+        &histogram.map(|share| (share / tallest, Color32::DARK_GREEN)),
+    );
+    let points = distribution
+        .curve()
+        .into_iter()
+        .map(|(share, equity)| {
+            egui::pos2(
+                rect.left() + share as f32 * rect.width(),
+                rect.bottom() - equity as f32 * rect.height(),
+            )
+        })
+        .collect::<Vec<_>>();
+    ui.painter_at(rect)
+        .line(points, egui::Stroke::new(1.0_f32, Color32::ORANGE));
+}
+
+/// Table of the made hands and draws in a range, with their share and combinations
+pub fn composition_table(ui: &mut egui::Ui, composition: &crate::composition::Composition) {
+    egui::Grid::new("composition").striped(true).show(ui, |ui| {
+        for (holding, combos) in &composition.holdings {
+            ui.label(holding.to_string());
+            ui.label(format!("{:.1}%", composition.share(*holding) * 100.0));
+            ui.label(format!("{combos:.1} combos"));
+            ui.end_row();
+        }
+    });
+}
@@ -0,0 +1,1005 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::{cards::*, parse::Parse, state::*};
+
+/// A player sitting at the table when a hand was dealt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Seat {
+    pub number: usize,
+    pub player: String,
+    pub stack: usize,
+    pub sitting_out: bool,
+}
+
+/// Forced bets posted before the cards are dealt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Post {
+    Ante(usize),
+    SmallBlind(usize),
+    BigBlind(usize),
+    /// Both blinds posted at once, usually by a player returning to the table
+    SmallAndBig(usize),
+}
+
+impl Post {
+    pub fn amount(self) -> usize {
+        match self {
+            Post::Ante(a) | Post::SmallBlind(a) | Post::BigBlind(a) | Post::SmallAndBig(a) => a,
+        }
+    }
+}
+
+/// Name given to the user in hands recorded by the app
+pub const HERO: &str = "Hero";
+
+/// Names of the games in hand history headers and the variants they are played with. Names
+/// that start with another name come first.
+const GAMES: [(&str, Variant); 6] = [
+    ("6+ Hold'em No Limit", Variant::ShortDeck),
+    ("Hold'em No Limit", Variant::Holdem),
+    ("5 Card Omaha Hi/Lo Pot Limit", Variant::OmahaHiLo),
+    ("5 Card Omaha Pot Limit", Variant::Omaha),
+    ("Omaha Hi/Lo Pot Limit", Variant::OmahaHiLo),
+    ("Omaha Pot Limit", Variant::Omaha),
+];
+
+/// A single hand played at a table, as recorded in a hand history file.
+/// Amounts are in chips, or in cents when the game is played for a currency.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HandHistory {
+    pub id: u64,
+    /// Description of the game, such as "Hold'em No Limit ($0.01/$0.02 USD)"
+    pub game: String,
+    pub date: String,
+    pub table: String,
+    pub max_seats: Option<usize>,
+    pub button: usize,
+    pub currency: Option<char>,
+    pub small_blind: usize,
+    pub big_blind: usize,
+    pub seats: Vec<Seat>,
+    pub posts: Vec<(String, Post)>,
+    /// Owner of the history, whose hole cards are always known
+    pub hero: Option<String>,
+    pub hole_cards: Vec<(String, HoleCards)>,
+    pub actions: Vec<PlayerAction>,
+    pub board: Vec<Card>,
+    /// Bets no one called, returned to the player who made them
+    pub returned: Vec<(String, usize)>,
+    pub shown: Vec<(String, HoleCards)>,
+    pub mucked: Vec<String>,
+    pub collected: Vec<(String, usize)>,
+    pub total_pot: usize,
+    pub rake: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Header,
+    Betting(Street),
+    Showdown,
+    Summary,
+}
+
+/// Read and parse a hand history file, describing any failure
+pub fn read_histories(path: impl AsRef<Path>) -> Result<Vec<HandHistory>, String> {
+    std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| parse_histories(&text).map_err(|e| e.to_string()))
+}
+
+/// Parse every hand in a PokerStars style hand history file
+pub fn parse_histories(text: &str) -> Result<Vec<HandHistory>, ParseError> {
+    let mut hands = vec![];
+    let mut current: Option<(HandHistory, Section)> = None;
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim_start_matches('\u{feff}').trim();
+        let error = |message: String| ParseError {
+            line: number + 1,
+            message,
+        };
+        if line.starts_with("PokerStars ") && line.contains(" Hand #") {
+            hands.extend(current.take().map(|(hand, _)| hand));
+            current = Some((parse_header(line).map_err(error)?, Section::Header));
+        } else if let Some((hand, section)) = &mut current {
+            hand.parse_line(line, section).map_err(error)?;
+        } else if !line.is_empty() {
+            return Err(error(format!("expected a hand header, found '{line}'")));
+        }
+    }
+    hands.extend(current.map(|(hand, _)| hand));
+    Ok(hands)
+}
+
+fn parse_header(line: &str) -> Result<HandHistory, String> {
+    let invalid = || format!("invalid hand header '{line}'");
+    let (_, rest) = line.split_once(" Hand #").ok_or_else(invalid)?;
+    let (id, rest) = rest.split_once(':').ok_or_else(invalid)?;
+    let (game, date) = rest.trim().rsplit_once(" - ").ok_or_else(invalid)?;
+    // Blinds are the first parenthesized pair of amounts, e.g. "($0.01/$0.02 USD)" or "(10/20)"
+    let (small, big) = game
+        .split('(')
+        .skip(1)
+        .filter_map(|s| s.split(')').next()?.split_once('/'))
+        .next()
+        .ok_or_else(invalid)?;
+    let big = big.split_whitespace().next().ok_or_else(invalid)?;
+    let currency = small.chars().next().filter(|c| !c.is_ascii_digit());
+    Ok(HandHistory {
+        id: id.trim().parse().map_err(|_| invalid())?,
+        game: game.to_string(),
+        date: date.to_string(),
+        currency,
+        small_blind: parse_amount(small, currency)?,
+        big_blind: parse_amount(big, currency)?,
+        ..Default::default()
+    })
+}
+
+/// Parse an amount such as "$1.25" or "1,500" into cents or chips
+fn parse_amount(text: &str, currency: Option<char>) -> Result<usize, String> {
+    let invalid = || format!("invalid amount '{text}'");
+    let digits = text
+        .trim()
+        .trim_start_matches(|c: char| !c.is_ascii_digit())
+        .replace(',', "");
+    match (digits.split_once('.'), currency) {
+        (Some((whole, fraction)), _) if fraction.len() <= 2 => {
+            let whole = whole.parse::<usize>().map_err(|_| invalid())?;
+            let fraction = format!("{fraction:0<2}")
+                .parse::<usize>()
+                .map_err(|_| invalid())?;
+            Ok(whole * 100 + fraction)
+        }
+        (None, Some(_)) => Ok(digits.parse::<usize>().map_err(|_| invalid())? * 100),
+        (None, None) => digits.parse().map_err(|_| invalid()),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parse cards separated by spaces, such as "Ah Kd"
+fn parse_cards(text: &str) -> Result<Vec<Card>, String> {
+    text.split_whitespace()
+        .map(|card| {
+            let chars = &mut card.chars();
+            Card::parse(chars)
+                .filter(|_| chars.next().is_none())
+                .ok_or_else(|| format!("invalid card '{card}'"))
+        })
+        .collect()
+}
+
+/// The cards inside the last pair of square brackets on a line
+fn last_bracketed(line: &str) -> Result<Vec<Card>, String> {
+    let start = line.rfind('[').ok_or("missing cards")?;
+    let end = line[start..].find(']').ok_or("missing cards")? + start;
+    parse_cards(&line[start + 1..end])
+}
+
+/// Two hole cards in hold'em, or four or five in Omaha
+fn hole_cards(line: &str) -> Result<HoleCards, String> {
+    HoleCards::new(&last_bracketed(line)?)
+        .ok_or_else(|| format!("expected two to five hole cards in '{line}'"))
+}
+
+impl HandHistory {
+    /// Variant the hand was played with, unless the game isn't supported
+    pub fn variant(&self) -> Option<Variant> {
+        GAMES
+            .iter()
+            .find(|(name, _)| self.game.starts_with(name))
+            .map(|(_, variant)| *variant)
+    }
+
+    /// Find the seated player a line is about, followed by the rest of the line
+    fn split_player<'a>(&self, line: &'a str, separator: &str) -> Option<(String, &'a str)> {
+        self.seats
+            .iter()
+            .filter(|s| {
+                line.starts_with(&s.player) && line[s.player.len()..].starts_with(separator)
+            })
+            .max_by_key(|s| s.player.len())
+            .map(|s| (s.player.clone(), &line[s.player.len() + separator.len()..]))
+    }
+
+    fn parse_line(&mut self, line: &str, section: &mut Section) -> Result<(), String> {
+        let amount = |text: &str| parse_amount(text, self.currency);
+        if let Some(marker) = line.strip_prefix("*** ") {
+            *section = match marker.split(" ***").next().unwrap_or_default() {
+                "HOLE CARDS" => Section::Betting(Street::PreFlop),
+                "FLOP" | "TURN" | "RIVER" => {
+                    self.board.extend(last_bracketed(line)?);
+                    let street = match self.board.len() {
+                        3 => Street::Flop,
+                        4 => Street::Turn,
+                        5 => Street::River,
+                        _ => return Err(format!("unexpected board in '{line}'")),
+                    };
+                    Section::Betting(street)
+                }
+                "SHOW DOWN" => Section::Showdown,
+                "SUMMARY" => Section::Summary,
+                _ => return Err(format!("unsupported section '{line}'")),
+            };
+            return Ok(());
+        }
+        if let Some(rest) = line.strip_prefix("Table '") {
+            let (table, rest) = rest.split_once('\'').ok_or("invalid table")?;
+            self.table = table.to_string();
+            self.max_seats = rest
+                .split_whitespace()
+                .find_map(|word| word.strip_suffix("-max")?.parse().ok());
+            let button = rest.split_once("Seat #").ok_or("missing button seat")?.1;
+            self.button = button
+                .split_whitespace()
+                .next()
+                .and_then(|n| n.parse().ok())
+                .ok_or("invalid button seat")?;
+            return Ok(());
+        }
+        if *section == Section::Header
+            && let Some(rest) = line.strip_prefix("Seat ")
+        {
+            let (number, rest) = rest.split_once(": ").ok_or("invalid seat")?;
+            let chips = rest.find(" in chips").ok_or("missing chip count")?;
+            let open = rest[..chips].rfind(" (").ok_or("missing chip count")?;
+            self.seats.push(Seat {
+                number: number
+                    .parse()
+                    .map_err(|_| format!("invalid seat '{number}'"))?,
+                player: rest[..open].to_string(),
+                stack: amount(&rest[open + 2..chips])?,
+                sitting_out: rest.ends_with("is sitting out"),
+            });
+            return Ok(());
+        }
+        if *section == Section::Summary {
+            if let Some(rest) = line.strip_prefix("Total pot ") {
+                let mut parts = rest.split(" | ");
+                self.total_pot = amount(parts.next().unwrap_or_default())?;
+                if let Some(rake) = parts.find_map(|p| p.strip_prefix("Rake ")) {
+                    self.rake = amount(rake)?;
+                }
+            }
+            return Ok(());
+        }
+        if let Some(rest) = line.strip_prefix("Dealt to ")
+            && let Some(end) = rest.rfind(" [")
+        {
+            let name = &rest[..end];
+            let cards = hole_cards(line)?;
+            // Cards are dealt to the owner of the history first
+            if self.hero.is_none() {
+                self.hero = Some(name.to_string());
+            }
+            self.hole_cards.push((name.to_string(), cards));
+            return Ok(());
+        }
+        if let Some(rest) = line.strip_prefix("Uncalled bet (") {
+            let (bet, player) = rest
+                .split_once(") returned to ")
+                .ok_or("invalid uncalled bet")?;
+            self.returned.push((player.to_string(), amount(bet)?));
+            return Ok(());
+        }
+        if let Some((player, rest)) = self.split_player(line, " collected ") {
+            let won = rest.split_whitespace().next().unwrap_or_default();
+            self.collected.push((player, amount(won)?));
+            return Ok(());
+        }
+        let Some((player, rest)) = self.split_player(line, ": ") else {
+            // Chat messages and table notices
+            return Ok(());
+        };
+        let rest = rest.trim_end_matches(" and is all-in");
+        let mut words = rest.split_whitespace();
+        let (verb, argument) = (words.next().unwrap_or_default(), words.next());
+        let argument = || argument.ok_or(format!("missing amount in '{line}'"));
+        if verb == "shows" {
+            self.shown.push((player, hole_cards(line)?));
+            return Ok(());
+        }
+        if verb == "mucks" {
+            self.mucked.push(player);
+            return Ok(());
+        }
+        if verb == "posts" {
+            let post = if let Some(ante) = rest.strip_prefix("posts the ante ") {
+                Post::Ante(amount(ante)?)
+            } else if let Some(blind) = rest.strip_prefix("posts small & big blinds ") {
+                Post::SmallAndBig(amount(blind)?)
+            } else if let Some(blind) = rest.strip_prefix("posts small blind ") {
+                Post::SmallBlind(amount(blind)?)
+            } else if let Some(blind) = rest.strip_prefix("posts big blind ") {
+                Post::BigBlind(amount(blind)?)
+            } else {
+                return Err(format!("unknown blind in '{line}'"));
+            };
+            self.posts.push((player, post));
+            return Ok(());
+        }
+        let Section::Betting(street) = *section else {
+            return Ok(());
+        };
+        let action = match verb {
+            "folds" => Action::Fold,
+            "checks" => Action::Check,
+            "calls" => Action::Call(amount(argument()?)?),
+            "bets" => Action::Bet(amount(argument()?)?),
+            "raises" => {
+                let to = rest.split(" to ").nth(1).ok_or("missing raise total")?;
+                Action::Raise(amount(to)?)
+            }
+            _ => return Ok(()),
+        };
+        self.actions.push(PlayerAction {
+            player,
+            street,
+            action,
+        });
+        Ok(())
+    }
+}
+
+impl HandHistory {
+    /// A heads-up hand entered in the app, between the hero and a single opponent. `stacks`
+    /// are the chips the hero and the villain have behind at the end of the hand.
+    pub fn heads_up(
+        id: u64,
+        big_blind: usize,
+        state: DeckState,
+        hero_is_big_blind: bool,
+        (hero_stack, villain_stack): (usize, usize),
+        villain: &str,
+        actions: &[PlayerAction],
+    ) -> Self {
+        let (button, big) = if hero_is_big_blind {
+            (villain, HERO)
+        } else {
+            (HERO, villain)
+        };
+        let five_card = state.hand.cards().len() == 5;
+        let (game, _) = GAMES
+            .iter()
+            .find(|(name, variant)| {
+                *variant == state.variant && name.starts_with("5 Card") == five_card
+            })
+            .unwrap_or(&GAMES[1]);
+        let mut this = HandHistory {
+            id,
+            game: format!("{game} ({}/{big_blind})", big_blind / 2),
+            hole_cards: vec![(HERO.to_string(), state.hand)],
+            board: state.board.into_iter().collect(),
+            ..Self::heads_up_actions(big_blind, hero_is_big_blind, villain, actions)
+        };
+        // Stacks are only known after the hand, so add back whatever was put in the pot
+        let contributed = this.contributions();
+        for (number, player) in [(1, button), (2, big)] {
+            let behind = if player == HERO {
+                hero_stack
+            } else {
+                villain_stack
+            };
+            this.seats.push(Seat {
+                number,
+                player: player.to_string(),
+                stack: behind + contributed.get(player).copied().unwrap_or_default(),
+                sitting_out: false,
+            });
+        }
+        this.total_pot = contributed.values().sum();
+        this
+    }
+
+    /// Heads up hand between the hero and `villain` with only the blinds and `actions`, enough
+    /// to follow the chips each player puts in
+    pub fn heads_up_actions(
+        big_blind: usize,
+        hero_is_big_blind: bool,
+        villain: &str,
+        actions: &[PlayerAction],
+    ) -> Self {
+        let (button, big) = if hero_is_big_blind {
+            (villain, HERO)
+        } else {
+            (HERO, villain)
+        };
+        let small_blind = big_blind / 2;
+        HandHistory {
+            date: format_date(
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs()),
+            ),
+            table: "holdem".to_string(),
+            max_seats: Some(2),
+            button: 1,
+            small_blind,
+            big_blind,
+            posts: vec![
+                (button.to_string(), Post::SmallBlind(small_blind)),
+                (big.to_string(), Post::BigBlind(big_blind)),
+            ],
+            hero: Some(HERO.to_string()),
+            actions: actions.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    /// Total chips each player put in the pot
+    fn contributions(&self) -> HashMap<String, usize> {
+        let mut totals: HashMap<String, usize> = HashMap::new();
+        for (player, post) in &self.posts {
+            *totals.entry(player.clone()).or_default() += post.amount();
+        }
+        for street in Street::ALL {
+            let start = self.street_start(street);
+            for (player, put_in) in self.street_put_in(street) {
+                let added = put_in - start.get(player).copied().unwrap_or_default();
+                *totals.entry(player.to_string()).or_default() += added;
+            }
+        }
+        for (player, amount) in &self.returned {
+            let total = totals.entry(player.clone()).or_default();
+            *total = total.saturating_sub(*amount);
+        }
+        totals
+    }
+
+    /// Chips each player has in front of them on `street` after the actions so far
+    pub fn street_put_in(&self, street: Street) -> HashMap<&str, usize> {
+        let mut put_in = self.street_start(street);
+        for a in self.actions.iter().filter(|a| a.street == street) {
+            let chips = put_in.entry(a.player.as_str()).or_default();
+            *chips += match a.action {
+                Action::Call(amount) | Action::Bet(amount) => amount,
+                Action::Raise(to) => to.saturating_sub(*chips),
+                Action::Fold | Action::Check => 0,
+            };
+        }
+        put_in
+    }
+
+    /// Action of `player` bringing the chips they have in front of them on `street` up to
+    /// `to`: a check or a bet when nobody has chips in front of them, otherwise a check, a call
+    /// of the rest of the largest amount or a raise to `to`
+    pub fn action_to(&self, player: &str, street: Street, to: usize) -> Action {
+        let put_in = self.street_put_in(street);
+        let own = put_in.get(player).copied().unwrap_or_default();
+        let highest = put_in.values().copied().max().unwrap_or_default();
+        if to <= own {
+            Action::Check
+        } else if highest == 0 {
+            Action::Bet(to)
+        } else if to <= highest {
+            Action::Call(to - own)
+        } else {
+            Action::Raise(to)
+        }
+    }
+
+    /// Chips each player has in front of them at the start of a street
+    pub fn street_start(&self, street: Street) -> HashMap<&str, usize> {
+        let mut totals = HashMap::new();
+        if street == Street::PreFlop {
+            for (player, post) in &self.posts {
+                let blind = match post {
+                    Post::Ante(_) => 0,
+                    Post::SmallAndBig(_) => self.big_blind,
+                    post => post.amount(),
+                };
+                *totals.entry(player.as_str()).or_default() += blind;
+            }
+        }
+        totals
+    }
+
+    fn format_amount(&self, amount: usize) -> String {
+        match self.currency {
+            Some(symbol) => format!("{symbol}{}.{:02}", amount / 100, amount % 100),
+            None => format!("{amount}"),
+        }
+    }
+}
+
+/// Format seconds since the unix epoch as a UTC date and time
+fn format_date(secs: u64) -> String {
+    // Civil date from days since the epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (secs / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    let time = secs % 86400;
+    format!(
+        "{year}/{month:02}/{day:02} {}:{:02}:{:02} UTC",
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+impl std::fmt::Display for HandHistory {
+    /// Write the hand in the PokerStars hand history format
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let amount = |a: usize| self.format_amount(a);
+        let cards = |cards: &[Card]| {
+            cards
+                .iter()
+                .map(|c| format!("{}{}", c.0, c.1.to_string().to_lowercase()))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        writeln!(
+            f,
+            "PokerStars Hand #{}: {} - {}",
+            self.id, self.game, self.date
+        )?;
+        write!(f, "Table '{}'", self.table)?;
+        if let Some(max_seats) = self.max_seats {
+            write!(f, " {max_seats}-max")?;
+        }
+        writeln!(f, " Seat #{} is the button", self.button)?;
+        for seat in &self.seats {
+            write!(
+                f,
+                "Seat {}: {} ({} in chips)",
+                seat.number,
+                seat.player,
+                amount(seat.stack)
+            )?;
+            if seat.sitting_out {
+                write!(f, " is sitting out")?;
+            }
+            writeln!(f)?;
+        }
+        for (player, post) in &self.posts {
+            let kind = match post {
+                Post::Ante(_) => "the ante",
+                Post::SmallBlind(_) => "small blind",
+                Post::BigBlind(_) => "big blind",
+                Post::SmallAndBig(_) => "small & big blinds",
+            };
+            writeln!(f, "{player}: posts {kind} {}", amount(post.amount()))?;
+        }
+        writeln!(f, "*** HOLE CARDS ***")?;
+        for (player, hole) in &self.hole_cards {
+            writeln!(f, "Dealt to {player} [{}]", cards(hole.cards()))?;
+        }
+
+        // Track chips behind and in front of each player to write raises and all-ins
+        let mut behind = self
+            .seats
+            .iter()
+            .map(|s| (s.player.as_str(), s.stack))
+            .collect::<HashMap<_, _>>();
+        for (player, post) in &self.posts {
+            if let Some(stack) = behind.get_mut(player.as_str()) {
+                *stack = stack.saturating_sub(post.amount());
+            }
+        }
+        for street in Street::ALL {
+            let dealt = match street {
+                Street::PreFlop => 0,
+                Street::Flop => 3,
+                Street::Turn => 4,
+                Street::River => 5,
+            };
+            if self.board.len() < dealt {
+                break;
+            }
+            match street {
+                Street::PreFlop => (),
+                Street::Flop => writeln!(f, "*** FLOP *** [{}]", cards(&self.board[..3]))?,
+                Street::Turn | Street::River => writeln!(
+                    f,
+                    "*** {} *** [{}] [{}]",
+                    street.to_string().to_uppercase(),
+                    cards(&self.board[..dealt - 1]),
+                    cards(&self.board[dealt - 1..dealt])
+                )?,
+            }
+            let mut in_front = self.street_start(street);
+            let mut highest = in_front.values().copied().max().unwrap_or_default();
+            for a in self.actions.iter().filter(|a| a.street == street) {
+                let player = a.player.as_str();
+                let put_in = in_front.entry(player).or_default();
+                let (text, added) = match a.action {
+                    Action::Fold => ("folds".to_string(), 0),
+                    Action::Check => ("checks".to_string(), 0),
+                    Action::Call(c) => (format!("calls {}", amount(c)), c),
+                    Action::Bet(b) => (format!("bets {}", amount(b)), b),
+                    Action::Raise(to) => (
+                        format!(
+                            "raises {} to {}",
+                            amount(to.saturating_sub(highest)),
+                            amount(to)
+                        ),
+                        to.saturating_sub(*put_in),
+                    ),
+                };
+                *put_in += added;
+                highest = highest.max(*put_in);
+                let stack = behind.entry(player).or_default();
+                *stack = stack.saturating_sub(added);
+                let all_in = if added > 0 && *stack == 0 {
+                    " and is all-in"
+                } else {
+                    ""
+                };
+                writeln!(f, "{player}: {text}{all_in}")?;
+            }
+        }
+        for (player, returned) in &self.returned {
+            writeln!(
+                f,
+                "Uncalled bet ({}) returned to {player}",
+                amount(*returned)
+            )?;
+        }
+        if !self.shown.is_empty() || !self.mucked.is_empty() {
+            writeln!(f, "*** SHOW DOWN ***")?;
+        }
+        for (player, hole) in &self.shown {
+            write!(f, "{player}: shows [{}]", cards(hole.cards()))?;
+            if let (Some(variant), true) = (self.variant(), self.board.len() >= 3) {
+                write!(f, " ({})", variant.best_rank_for(hole.cards(), &self.board))?;
+            }
+            writeln!(f)?;
+        }
+        for player in &self.mucked {
+            writeln!(f, "{player}: mucks hand")?;
+        }
+        for (player, won) in &self.collected {
+            writeln!(f, "{player} collected {} from pot", amount(*won))?;
+        }
+        writeln!(f, "*** SUMMARY ***")?;
+        writeln!(
+            f,
+            "Total pot {} | Rake {}",
+            amount(self.total_pot),
+            amount(self.rake)
+        )?;
+        if !self.board.is_empty() {
+            writeln!(f, "Board [{}]", cards(&self.board))?;
+        }
+        for seat in &self.seats {
+            let player = seat.player.as_str();
+            let role = if seat.number == self.button {
+                " (button)"
+            } else if self
+                .posts
+                .iter()
+                .any(|(p, post)| p == player && matches!(post, Post::SmallBlind(_)))
+            {
+                " (small blind)"
+            } else if self
+                .posts
+                .iter()
+                .any(|(p, post)| p == player && matches!(post, Post::BigBlind(_)))
+            {
+                " (big blind)"
+            } else {
+                ""
+            };
+            let won = self
+                .collected
+                .iter()
+                .filter(|(p, _)| p == player)
+                .map(|(_, won)| won)
+                .sum::<usize>();
+            let shown = self.shown.iter().find(|(p, _)| p == player);
+            let folded = self
+                .actions
+                .iter()
+                .find(|a| a.player == player && a.action == Action::Fold);
+            let result = match (shown, folded) {
+                (Some((_, hole)), _) if won > 0 => {
+                    format!("showed [{}] and won ({})", cards(hole.cards()), amount(won))
+                }
+                (Some((_, hole)), _) => format!("showed [{}] and lost", cards(hole.cards())),
+                (None, _) if won > 0 => format!("collected ({})", amount(won)),
+                (None, Some(fold)) if fold.street == Street::PreFlop => {
+                    "folded before Flop".to_string()
+                }
+                (None, Some(fold)) => format!("folded on the {}", fold.street),
+                (None, None) if self.mucked.iter().any(|p| p == player) => "mucked".to_string(),
+                (None, None) => continue,
+            };
+            writeln!(f, "Seat {}: {player}{role} {result}", seat.number)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOLDEM: &str = "\
+PokerStars Hand #212345678901:  Hold'em No Limit ($0.01/$0.02 USD) - 2020/03/14 19:22:05 CET [2020/03/14 14:22:05 ET]
+Table 'Aaltje II' 6-max Seat #3 is the button
+Seat 1: Villain1 ($2.04 in chips)
+Seat 2: HeroName ($2 in chips)
+Seat 3: Villain3 ($1.87 in chips)
+Seat 5: Villain5 ($2.31 in chips)
+Seat 6: Villain6 ($0.96 in chips) is sitting out
+Villain5: posts small blind $0.01
+Villain1: posts big blind $0.02
+*** HOLE CARDS ***
+Dealt to HeroName [Ah Qd]
+HeroName: raises $0.04 to $0.06
+Villain3: folds
+Villain5: folds
+Villain1: calls $0.04
+*** FLOP *** [Qs 8d 3c]
+Villain1: checks
+HeroName: bets $0.08
+Villain1: calls $0.08
+*** TURN *** [Qs 8d 3c] [2h]
+Villain1: checks
+HeroName: bets $0.19
+Villain1: calls $0.19
+*** RIVER *** [Qs 8d 3c 2h] [Kc]
+Villain1: checks
+HeroName: checks
+*** SHOW DOWN ***
+Villain1: shows [Qc Jc] (a pair of Queens)
+HeroName: shows [Ah Qd] (a pair of Queens - Ace kicker)
+HeroName collected $0.65 from pot
+*** SUMMARY ***
+Total pot $0.67 | Rake $0.02
+Board [Qs 8d 3c 2h Kc]
+Seat 1: Villain1 (big blind) showed [Qc Jc] and lost with a pair of Queens
+Seat 2: HeroName showed [Ah Qd] and won ($0.65) with a pair of Queens
+Seat 3: Villain3 (button) folded before Flop (didn't bet)
+Seat 5: Villain5 (small blind) folded before Flop
+Seat 6: Villain6 is sitting out
+";
+
+    const OMAHA: &str = "\
+PokerStars Hand #212345678902:  Omaha Pot Limit ($0.02/$0.05 USD) - 2020/03/14 19:25:41 CET [2020/03/14 14:25:41 ET]
+Table 'Bellatrix' 6-max Seat #1 is the button
+Seat 1: HeroName ($5 in chips)
+Seat 2: Villain1 ($4.80 in chips)
+Seat 3: Villain3 ($5.12 in chips)
+Villain1: posts small blind $0.02
+Villain3: posts big blind $0.05
+*** HOLE CARDS ***
+Dealt to HeroName [As Ks Jd Th]
+HeroName: raises $0.12 to $0.17
+Villain1: folds
+Villain3: calls $0.12
+*** FLOP *** [Qs 9s 4d]
+Villain3: bets $0.36
+HeroName: calls $0.36
+*** TURN *** [Qs 9s 4d] [2c]
+Villain3: bets $1.06
+HeroName: folds
+Uncalled bet ($1.06) returned to Villain3
+Villain3 collected $1.05 from pot
+Villain3: doesn't show hand
+*** SUMMARY ***
+Total pot $1.08 | Rake $0.03
+Board [Qs 9s 4d 2c]
+Seat 1: HeroName (button) folded on the Turn
+Seat 2: Villain1 (small blind) folded before Flop
+Seat 3: Villain3 (big blind) collected ($1.05)
+";
+
+    fn hole(cards: &[Card]) -> HoleCards {
+        HoleCards::new(cards).unwrap()
+    }
+
+    /// Hold'em and Omaha hands in one file are both read
+    #[test]
+    fn parse_pokerstars() {
+        let hands = parse_histories(&format!("{HOLDEM}\n\n{OMAHA}")).unwrap();
+        assert_eq!(hands.len(), 2);
+
+        let holdem = &hands[0];
+        assert_eq!(holdem.id, 212345678901);
+        assert_eq!(holdem.variant(), Some(Variant::Holdem));
+        assert_eq!(holdem.table, "Aaltje II");
+        assert_eq!((holdem.max_seats, holdem.button), (Some(6), 3));
+        assert_eq!(holdem.currency, Some('$'));
+        assert_eq!((holdem.small_blind, holdem.big_blind), (1, 2));
+        assert_eq!(holdem.seats.len(), 5);
+        assert_eq!(
+            (holdem.seats[1].player.as_str(), holdem.seats[1].stack),
+            ("HeroName", 200)
+        );
+        assert!(holdem.seats[4].sitting_out);
+        assert_eq!(
+            holdem.posts,
+            [
+                ("Villain5".to_string(), Post::SmallBlind(1)),
+                ("Villain1".to_string(), Post::BigBlind(2)),
+            ]
+        );
+        assert_eq!(holdem.hero.as_deref(), Some("HeroName"));
+        assert_eq!(
+            holdem.hole_cards,
+            [("HeroName".to_string(), hole(&cards!("Ah", "Qd")))]
+        );
+        assert_eq!(holdem.actions.len(), 12);
+        assert_eq!(
+            holdem.actions[0],
+            PlayerAction {
+                player: "HeroName".to_string(),
+                street: Street::PreFlop,
+                action: Action::Raise(6),
+            }
+        );
+        assert_eq!(
+            holdem.actions[9],
+            PlayerAction {
+                player: "Villain1".to_string(),
+                street: Street::Turn,
+                action: Action::Call(19),
+            }
+        );
+        assert_eq!(holdem.board, cards!("Qs", "8d", "3c", "2h", "Kc"));
+        assert_eq!(
+            holdem.shown,
+            [
+                ("Villain1".to_string(), hole(&cards!("Qc", "Jc"))),
+                ("HeroName".to_string(), hole(&cards!("Ah", "Qd"))),
+            ]
+        );
+        assert_eq!(holdem.collected, [("HeroName".to_string(), 65)]);
+        assert_eq!((holdem.total_pot, holdem.rake), (67, 2));
+        assert_eq!(holdem.contributions()["Villain1"], 33);
+
+        let omaha = &hands[1];
+        assert_eq!(omaha.variant(), Some(Variant::Omaha));
+        assert_eq!(
+            omaha.hole_cards,
+            [(
+                "HeroName".to_string(),
+                hole(&cards!("As", "Ks", "Jd", "Th"))
+            )]
+        );
+        assert_eq!(omaha.returned, [("Villain3".to_string(), 106)]);
+        assert_eq!(omaha.collected, [("Villain3".to_string(), 105)]);
+        assert_eq!((omaha.total_pot, omaha.rake), (108, 3));
+    }
+
+    /// Errors point at the line that couldn't be read
+    #[test]
+    fn parse_error_line() {
+        let text = HOLDEM.replace("Dealt to HeroName [Ah Qd]", "Dealt to HeroName [Ah Qx]");
+        assert_eq!(parse_histories(&text).unwrap_err().line, 11);
+        let text = format!(
+            "{HOLDEM}\n{}",
+            OMAHA.replace("[As Ks Jd Th]", "[As Ks Jd Th 9c 8c]")
+        );
+        assert_eq!(
+            parse_histories(&text).unwrap_err().line,
+            HOLDEM.lines().count() + 10
+        );
+    }
+
+    /// Hands entered in the app read back exactly as they were written
+    #[test]
+    fn export_round_trip() {
+        let act = |player: &str, street, action| PlayerAction {
+            player: player.to_string(),
+            street,
+            action,
+        };
+        let actions = [
+            act(HERO, Street::PreFlop, Action::Raise(6)),
+            act("Villain", Street::PreFlop, Action::Raise(18)),
+            act(HERO, Street::PreFlop, Action::Call(12)),
+            act("Villain", Street::Flop, Action::Bet(20)),
+            act(HERO, Street::Flop, Action::Raise(80)),
+            act("Villain", Street::Flop, Action::Call(60)),
+        ];
+        let board = cards!("Qs", "9s", "7d", "6c", "Kh");
+        for (variant, cards) in [
+            (Variant::Holdem, &cards!("Ah", "Qd")[..]),
+            (Variant::ShortDeck, &cards!("Ah", "Qd")[..]),
+            (Variant::Omaha, &cards!("As", "Ks", "Jd", "Th")[..]),
+            (
+                Variant::OmahaHiLo,
+                &cards!("As", "2s", "3d", "Th", "8c")[..],
+            ),
+        ] {
+            let state = DeckState {
+                board: Board::from_cards(&board).unwrap(),
+                hand: hole(cards),
+                variance: Variance::Normal,
+                dead: CardSet::default(),
+                variant,
+            };
+            let hand = HandHistory::heads_up(7, 2, state, false, (0, 150), "Villain", &actions);
+            assert_eq!(hand.variant(), Some(variant));
+            assert_eq!(hand.seats[0].stack, 98);
+            assert_eq!(hand.seats[1].stack, 248);
+            assert_eq!(parse_histories(&hand.to_string()), Ok(vec![hand]));
+        }
+    }
+
+    /// Actions entered as the chips a player brings in on each street export as bets, calls
+    /// and raises to the player's total on the street, as the app records them
+    #[test]
+    fn export_app_actions() {
+        let mut actions = vec![];
+        let mut play = |player: &str, street, added: usize| {
+            let history = HandHistory::heads_up_actions(2, false, "Villain", &actions);
+            let put_in = history.street_put_in(street);
+            let to = put_in.get(player).copied().unwrap_or_default() + added;
+            actions.push(PlayerAction {
+                player: player.to_string(),
+                street,
+                action: history.action_to(player, street, to),
+            });
+        };
+        play(HERO, Street::PreFlop, 5);
+        play("Villain", Street::PreFlop, 16);
+        play(HERO, Street::PreFlop, 12);
+        play("Villain", Street::Flop, 0);
+        play(HERO, Street::Flop, 50);
+        play("Villain", Street::Flop, 150);
+        play(HERO, Street::Flop, 232);
+        play("Villain", Street::Flop, 132);
+        assert_eq!(
+            actions.iter().map(|a| a.action).collect::<Vec<_>>(),
+            [
+                Action::Raise(6),
+                Action::Raise(18),
+                Action::Call(12),
+                Action::Check,
+                Action::Bet(50),
+                Action::Raise(150),
+                Action::Raise(282),
+                Action::Call(132),
+            ]
+        );
+
+        let state = DeckState {
+            board: Board::from_cards(&cards!("Qs", "9s", "4d")).unwrap(),
+            hand: hole(&cards!("Ah", "Qd")),
+            variance: Variance::Normal,
+            dead: CardSet::default(),
+            variant: Variant::Holdem,
+        };
+        let hand = HandHistory::heads_up(7, 2, state, false, (0, 100), "Villain", &actions);
+        assert_eq!(hand.seats[0].stack, 300);
+        assert_eq!(hand.total_pot, 600);
+        let text = hand.to_string();
+        for line in [
+            format!("{HERO}: raises 4 to 6"),
+            format!("{HERO}: bets 50"),
+            "Villain: raises 100 to 150".to_string(),
+            format!("{HERO}: raises 132 to 282 and is all-in"),
+        ] {
+            assert!(text.contains(&line), "{line} missing from {text}");
+        }
+        assert_eq!(parse_histories(&text), Ok(vec![hand]));
+    }
+}
@@ -0,0 +1,256 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use itertools::Itertools;
+
+use crate::cards::*;
+
+const RANKS: usize = 13;
+
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1u128, |acc, i| acc * (n - i) as u128 / (i + 1) as u128) as usize
+}
+
+fn rank_of(card: Card) -> usize {
+    card.0.high() - 2
+}
+
+fn suit_of(card: Card) -> usize {
+    card.1 as usize - 1
+}
+
+/// Index of `set` among the sets of the same size drawn from the ranks not in `used`
+fn set_index(set: u16, used: u16) -> usize {
+    let mut index = 0;
+    let mut drawn = 0;
+    for rank in 0..RANKS {
+        if set & 1 << rank != 0 {
+            let position = rank - (used & ((1 << rank) - 1)).count_ones() as usize;
+            drawn += 1;
+            index += binomial(position, drawn);
+        }
+    }
+    index
+}
+
+/// Cards of one suit dealt in each round, as bit sets of ranks
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct SuitCards {
+    /// Number of cards of the suit in each round
+    shape: Vec<usize>,
+    /// Index of the suit's cards among every way to deal `shape`
+    index: usize,
+    /// Number of ways to deal `shape`
+    size: usize,
+}
+
+impl SuitCards {
+    fn new(sets: &[u16]) -> Self {
+        let mut used = 0u16;
+        let (mut index, mut size) = (0, 1);
+        let mut shape = vec![];
+        for set in sets {
+            let count = set.count_ones() as usize;
+            let ways = binomial(RANKS - used.count_ones() as usize, count);
+            index += set_index(*set, used) * size;
+            size *= ways;
+            used |= set;
+            shape.push(count);
+        }
+        SuitCards { shape, index, size }
+    }
+}
+
+/// Cards of each suit in every round, from the suit that sorts first when canonical
+fn suits(rounds: &[&[Card]]) -> Vec<(usize, SuitCards)> {
+    let mut sets = vec![vec![0u16; rounds.len()]; 4];
+    for (round, cards) in rounds.iter().enumerate() {
+        for card in cards.iter() {
+            sets[suit_of(*card)][round] |= 1 << rank_of(*card);
+        }
+    }
+    let mut suits = sets
+        .iter()
+        .enumerate()
+        .map(|(suit, sets)| (suit, SuitCards::new(sets)))
+        .collect::<Vec<_>>();
+    suits.sort_by(|a, b| (&b.1.shape, b.1.index).cmp(&(&a.1.shape, a.1.index)));
+    suits
+}
+
+/// Canonical relabelling of the suits: the suit each suit is exchanged for so that every deal
+/// that only differs by exchanging suits looks the same
+pub fn suit_permutation(rounds: &[&[Card]]) -> [Suite; 4] {
+    let mut permutation = Suite::ALL;
+    for (position, (suit, _)) in suits(rounds).into_iter().enumerate() {
+        permutation[suit] = Suite::ALL[position];
+    }
+    permutation
+}
+
+/// Dense index of deals of a fixed number of cards per round, such as hole cards then the flop,
+/// up to exchanging suits
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandIndexer {
+    rounds: Vec<usize>,
+    /// First index of each way of spreading the cards over the suits, by shapes in canonical
+    /// suit order
+    offsets: HashMap<Vec<Vec<usize>>, usize>,
+}
+
+impl HandIndexer {
+    pub fn new(rounds: &[usize]) -> Self {
+        // Every spread of the cards of each round over the suits, with the suits in order
+        let mut configurations = BTreeSet::from([vec![vec![]; 4]]);
+        for cards in rounds.iter().copied() {
+            let mut next = BTreeSet::new();
+            for configuration in &configurations {
+                for counts in (0..4).map(|_| 0..=cards).multi_cartesian_product() {
+                    if counts.iter().sum::<usize>() != cards {
+                        continue;
+                    }
+                    let mut shapes: Vec<Vec<usize>> = configuration.clone();
+                    for (shape, count) in shapes.iter_mut().zip(counts) {
+                        shape.push(count);
+                    }
+                    if shapes.iter().any(|s| s.iter().sum::<usize>() > RANKS) {
+                        continue;
+                    }
+                    shapes.sort_by(|a, b| b.cmp(a));
+                    next.insert(shapes);
+                }
+            }
+            configurations = next;
+        }
+        let mut offsets = HashMap::new();
+        let mut offset = 0;
+        for shapes in configurations {
+            let configuration_size = shapes
+                .iter()
+                .chunk_by(|s| *s)
+                .into_iter()
+                .map(|(shape, group)| {
+                    let ways = shape
+                        .iter()
+                        .scan(0, |used, count| {
+                            let ways = binomial(RANKS - *used, *count);
+                            *used += count;
+                            Some(ways)
+                        })
+                        .product::<usize>();
+                    multisets(ways, group.count())
+                })
+                .product::<usize>();
+            offsets.insert(shapes, offset);
+            offset += configuration_size;
+        }
+        HandIndexer {
+            rounds: rounds.to_vec(),
+            offsets,
+        }
+    }
+
+    /// Dense index of the deal, the same for every deal that only differs by
+    /// exchanging suits
+    pub fn index(&self, rounds: &[&[Card]]) -> usize {
+        assert!(
+            rounds
+                .iter()
+                .map(|r| r.len())
+                .eq(self.rounds.iter().copied()),
+            "Deal doesn't match the indexer's rounds"
+        );
+        let suits = suits(rounds);
+        let shapes = suits
+            .iter()
+            .map(|(_, s)| s.shape.clone())
+            .collect::<Vec<_>>();
+        let mut index = 0;
+        for (_, group) in &suits.iter().map(|(_, s)| s).chunk_by(|s| &s.shape) {
+            let group = group.collect::<Vec<_>>();
+            // Suits with the same shape can be exchanged, so only the multiset of their
+            // indices matters
+            let multiset = group
+                .iter()
+                .map(|s| s.index)
+                .sorted()
+                .enumerate()
+                .map(|(j, i)| binomial(i + j, j + 1))
+                .sum::<usize>();
+            index = index * multisets(group[0].size, group.len()) + multiset;
+        }
+        self.offsets[&shapes] + index
+    }
+}
+
+/// Number of multisets of `k` items from `n`
+fn multisets(n: usize, k: usize) -> usize {
+    binomial(n + k - 1, k)
+}
+
+/// Indexers built so far, by cards per round
+static INDEXERS: LazyLock<Mutex<HashMap<Vec<usize>, Arc<HandIndexer>>>> =
+    LazyLock::new(Default::default);
+
+/// Shared indexer for deals with `rounds` cards per round
+pub fn indexer(rounds: &[usize]) -> Arc<HandIndexer> {
+    INDEXERS
+        .lock()
+        .unwrap()
+        .entry(rounds.to_vec())
+        .or_insert_with(|| Arc::new(HandIndexer::new(rounds)))
+        .clone()
+}
+
+/// One board of `n` cards from `deck` for each distinct way of dealing it next to the `fixed`
+/// cards, with the number of boards it stands for. With nothing fixed there are 1,755 distinct
+/// flops among the 22,100.
+pub fn distinct_boards(deck: &[Card], fixed: &[&[Card]], n: usize) -> Vec<(Vec<Card>, usize)> {
+    let rounds = fixed.iter().map(|r| r.len()).chain([n]).collect::<Vec<_>>();
+    let indexer = indexer(&rounds);
+    let mut boards: HashMap<usize, (Vec<Card>, usize)> = HashMap::new();
+    for board in deck.iter().copied().combinations(n) {
+        let deal = fixed
+            .iter()
+            .copied()
+            .chain([board.as_slice()])
+            .collect::<Vec<_>>();
+        boards.entry(indexer.index(&deal)).or_insert((board, 0)).1 += 1;
+    }
+    boards
+        .into_iter()
+        .sorted_by_key(|(index, _)| *index)
+        .map(|(_, board)| board)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Flops fall into 1,755 sets that only differ by suits, and hole cards into 169 classes
+    #[test]
+    fn distinct_flops() {
+        let flops = distinct_boards(&deck(), &[], 3);
+        assert_eq!(flops.len(), 1755);
+        assert_eq!(flops.iter().map(|(_, count)| count).sum::<usize>(), 22100);
+        assert_eq!(distinct_boards(&deck(), &[], 2).len(), 169);
+        let aces = cards!("As", "Ad");
+        let fixed: [&[Card]; 1] = [&aces];
+        let boards = distinct_boards(&deck_without(aces), &fixed, 3);
+        assert_eq!(boards.iter().map(|(_, count)| count).sum::<usize>(), 19600);
+        // Exchanging suits gives the same index
+        let flop = cards!("2c", "7c", "Kh");
+        let exchanged = cards!("2h", "7h", "Kc");
+        let indexer = indexer(&[2, 3]);
+        assert_eq!(
+            indexer.index(&[&aces, &flop]),
+            indexer.index(&[&cards!("Ad", "As"), &exchanged])
+        );
+    }
+}
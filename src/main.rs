@@ -3,28 +3,56 @@
 #[macro_use]
 mod cards;
 mod ai;
+mod cache;
+mod cli;
+mod composition;
+mod distribution;
+mod draws;
 mod eval;
 mod gui;
+mod history;
+mod iso;
+mod matchups;
 mod parse;
+mod plan;
 mod preflop;
+mod range;
 mod rank;
+mod review;
 mod state;
+mod stats;
+mod storage;
+mod streets;
+mod stud;
 
 use cards::*;
+use composition::Composition;
+use distribution::EquityDistribution;
 use egui::{Color32, RichText, Widget};
 use eval::*;
 use gui::*;
+use history::{HERO, HandHistory};
 use preflop::*;
+use range::*;
+use review::HandReview;
 use state::*;
+use stats::*;
+use streets::*;
+
+use std::sync::{Arc, Mutex};
 
 use crate::parse::Parse;
 
 fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
     let opts = eframe::NativeOptions {
         vsync: true,
         hardware_acceleration: eframe::HardwareAcceleration::Preferred,
         dithering: true,
-        viewport: egui::ViewportBuilder::default().with_inner_size((800.0, 1000.0)),
+        viewport: egui::ViewportBuilder::default().with_inner_size((1200.0, 1000.0)),
         ..Default::default()
     };
     const MAX_PLAYERS: usize = 9;
@@ -32,10 +60,13 @@ fn main() {
 
     let mut pocket_cards_input = String::new();
     let mut board_cards_input = String::new();
+    let mut dead_cards_input = String::new();
     let mut players_in = 5usize;
     let mut players_at_table = 5usize;
     let mut position = Position::default();
     let mut variance = Variance::Normal;
+    let mut variant = Variant::default();
+    let mut omaha_range = OmahaRange::default();
     let mut state: Option<DeckState> = None;
     let mut last_state: Option<DeckState> = None;
     let mut pot_input = String::new();
@@ -44,38 +75,409 @@ fn main() {
     let mut blind_input = format!("{blind}");
     let mut stack_input = String::new();
     let mut stack = 0usize;
+    let mut villain_stack_input = String::new();
     let mut call_price_input = String::new();
     let mut call_price = 0;
-    let mut strength_calc_thread: Option<std::thread::JoinHandle<f64>> = None;
+    // Strength and split pot equity, unless the calculation was cancelled
+    type Strength = Option<(f64, Option<HiLoEquity>)>;
+    let mut strength_calc_thread: Option<std::thread::JoinHandle<Strength>> = None;
+    let mut strength_cancel = CancelToken::default();
+    let mut strength_progress: Arc<Mutex<Option<Progress>>> = Default::default();
     let mut hand_strength: Option<f64> = None;
+    let mut hi_lo_equity: Option<HiLoEquity> = None;
+    let mut profile = Profile::default();
+    let mut villain_name = "Villain".to_string();
+    let mut use_stats = false;
+    let mut tracker = Tracker::load(storage::data_path("opponents.tsv")).unwrap_or_default();
+    let mut tracker_error: Option<String> = None;
+    let mut hand_actions: Vec<PlayerAction> = vec![];
+    let mut last_villain: (Vec<(Street, Action)>, Profile, OmahaRange) =
+        (vec![], profile, omaha_range);
+    let mut villain_range: Option<Range> = None;
+    let mut fold_model: Option<FoldModel> = None;
+    let mut blocker_report: Option<BlockerReport> = None;
+    let mut composition: Option<Composition> = None;
+    let mut in_position = false;
+    let mut left_to_act = 0usize;
+    let mut bet_sizes = ai::BetSize::MENU.map(|size| (size, true));
+    // Strength on each street and after each next card, unless the analysis was cancelled
+    type StreetAnalysis = Option<(Vec<(Street, f64)>, Vec<NextCard>)>;
+    let mut streets_thread: Option<std::thread::JoinHandle<StreetAnalysis>> = None;
+    let mut streets_cancel = CancelToken::default();
+    let mut streets_progress: Arc<Mutex<Option<Progress>>> = Default::default();
+    let mut street_analysis: StreetAnalysis = None;
+    let mut analyzed: Option<(DeckState, Range)> = None;
+    // The hand against each villain combination, and the villain range against the hero's
+    type Distributions = Option<(EquityDistribution, EquityDistribution)>;
+    let mut distribution_thread: Option<std::thread::JoinHandle<Distributions>> = None;
+    let mut distribution_cancel = CancelToken::default();
+    let mut distribution_progress: Arc<Mutex<Option<Progress>>> = Default::default();
+    let mut distributions: Distributions = None;
+    let mut distributed: Option<(DeckState, Range)> = None;
+    let mut review_path = String::new();
+    let mut review_thread: Option<std::thread::JoinHandle<Result<Vec<HandReview>, String>>> = None;
+    let mut reviews: Result<Vec<HandReview>, String> = Ok(vec![]);
+    let mut review_index = 0usize;
+    let mut review_step = 0usize;
+
+    // Boards of the preflop matchup table done and in total, while the first run calculates it
+    let matchups_progress: Arc<Mutex<Option<(usize, usize)>>> = Default::default();
+    let progress = matchups_progress.clone();
+    std::thread::spawn(move || {
+        matchups_or_calculate(|done, total| *progress.lock().unwrap() = Some((done, total)));
+    });
 
     eframe::run_simple_native("Poker Solver", opts, move |ctx, _frame| {
         ctx.set_pixels_per_point(2.0);
+        egui::SidePanel::right("analysis").show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                if let Some(range) = &villain_range {
+                    ui.label(emphasized("Villain range:", Color32::ORANGE));
+                    range_heatmap(ui, &range.class_weights());
+                }
+                if let Some(report) = &blocker_report {
+                    ui.label(emphasized("Blockers:", Color32::ORANGE));
+                    ui.label(report.to_string());
+                }
+                if let Some(composition) = composition.as_ref().filter(|c| c.total > 0.0) {
+                    ui.label(emphasized("Range composition:", Color32::ORANGE));
+                    composition_table(ui, composition);
+                }
+                if analyzed.as_ref().map(|(s, r)| (s, r))
+                    != last_state.as_ref().zip(villain_range.as_ref())
+                {
+                    // The hand analysed is no longer the one calculated
+                    streets_cancel.cancel();
+                    streets_thread = None;
+                    street_analysis = None;
+                    analyzed = None;
+                }
+                if streets_thread.as_ref().is_some_and(|t| t.is_finished()) {
+                    street_analysis = streets_thread.take().unwrap().join().unwrap();
+                }
+                if let (Some(state), Some(range)) = (last_state, &villain_range) {
+                    ui.separator();
+                    ui.label(emphasized("Equity by street:", Color32::ORANGE));
+                    if ui
+                        .add_enabled(
+                            streets_thread.is_none() && street_analysis.is_none(),
+                            egui::Button::new("analyze streets"),
+                        )
+                        .clicked()
+                    {
+                        analyzed = Some((state, range.clone()));
+                        streets_cancel = CancelToken::default();
+                        streets_progress = Default::default();
+                        let cancel = streets_cancel.clone();
+                        let shared_progress = streets_progress.clone();
+                        let range = if state.variant.omaha() {
+                            OpponentRange::Omaha(last_villain.2)
+                        } else {
+                            OpponentRange::Combos(range.clone())
+                        };
+                        let ctx = ctx.clone();
+                        streets_thread = Some(std::thread::spawn(move || {
+                            let progress = |progress: Progress| {
+                                *shared_progress.lock().unwrap() = Some(Progress {
+                                    done: progress.done / 2.0,
+                                    ..progress
+                                });
+                                ctx.request_repaint();
+                            };
+                            let streets = state.strength_by_street(&range, &cancel, &progress);
+                            let progress = |progress: Progress| {
+                                *shared_progress.lock().unwrap() = Some(Progress {
+                                    done: 0.5 + progress.done / 2.0,
+                                    ..progress
+                                });
+                                ctx.request_repaint();
+                            };
+                            let next_cards = state.next_card_strengths(&range, &cancel, &progress);
+                            ctx.request_repaint();
+                            Some((streets?, next_cards?))
+                        }));
+                    }
+                    if streets_thread.is_some() {
+                        let done = streets_progress.lock().unwrap().map_or(0.0, |p| p.done);
+                        ui.add(egui::ProgressBar::new(done as f32).show_percentage());
+                    }
+                }
+                if let Some((streets, next_cards)) = &street_analysis {
+                    line_chart(
+                        ui,
+                        &streets
+                            .iter()
+                            .map(|(street, strength)| (street.to_string(), *strength))
+                            .collect::<Vec<_>>(),
+                    );
+                    if !next_cards.is_empty() {
+                        ui.label(emphasized("Next card:", Color32::ORANGE));
+                        let groups = by_improvement(next_cards);
+                        bar_chart(
+                            ui,
+                            &groups
+                                .iter()
+                                .flat_map(|(improvement, cards)| {
+                                    cards.iter().map(|card| {
+                                        (card.strength, improvement_color(*improvement))
+                                    })
+                                })
+                                .collect::<Vec<_>>(),
+                        );
+                        for (improvement, cards) in &groups {
+                            let average =
+                                cards.iter().map(|c| c.strength).sum::<f64>() / cards.len() as f64;
+                            ui.colored_label(
+                                improvement_color(*improvement),
+                                format!(
+                                    "{}: {} cards, {:.1}%",
+                                    improvement.map_or("No improvement".to_string(), |kind| kind
+                                        .to_string()),
+                                    cards.len(),
+                                    average * 100.0
+                                ),
+                            );
+                        }
+                    }
+                }
+                if distributed.as_ref().map(|(s, r)| (s, r))
+                    != last_state.as_ref().zip(villain_range.as_ref())
+                {
+                    distribution_cancel.cancel();
+                    distribution_thread = None;
+                    distributions = None;
+                    distributed = None;
+                }
+                if distribution_thread
+                    .as_ref()
+                    .is_some_and(|t| t.is_finished())
+                {
+                    distributions = distribution_thread.take().unwrap().join().unwrap();
+                }
+                // Ranges only hold two card combinations
+                let hold_em_state = last_state.filter(|s| !s.variant.omaha());
+                if let (Some(state), Some(range)) = (hold_em_state, &villain_range) {
+                    ui.separator();
+                    ui.label(emphasized("Equity distribution:", Color32::ORANGE));
+                    if ui
+                        .add_enabled(
+                            distribution_thread.is_none() && distributions.is_none(),
+                            egui::Button::new("analyze distribution"),
+                        )
+                        .clicked()
+                    {
+                        distributed = Some((state, range.clone()));
+                        distribution_cancel = CancelToken::default();
+                        distribution_progress = Default::default();
+                        let cancel = distribution_cancel.clone();
+                        let shared_progress = distribution_progress.clone();
+                        let range = range.clone();
+                        let hero_range = Range::from(position.gto_preflop());
+                        let ctx = ctx.clone();
+                        distribution_thread = Some(std::thread::spawn(move || {
+                            let progress = |progress: Progress| {
+                                *shared_progress.lock().unwrap() = Some(Progress {
+                                    done: progress.done / 2.0,
+                                    ..progress
+                                });
+                                ctx.request_repaint();
+                            };
+                            let hero = state.equity_distribution(&range, &cancel, &progress);
+                            let progress = |progress: Progress| {
+                                *shared_progress.lock().unwrap() = Some(Progress {
+                                    done: 0.5 + progress.done / 2.0,
+                                    ..progress
+                                });
+                                ctx.request_repaint();
+                            };
+                            let villain =
+                                state.range_distribution(&range, &hero_range, &cancel, &progress);
+                            ctx.request_repaint();
+                            Some((hero?, villain?))
+                        }));
+                    }
+                    if distribution_thread.is_some() {
+                        let done = distribution_progress
+                            .lock()
+                            .unwrap()
+                            .map_or(0.0, |p| p.done);
+                        ui.add(egui::ProgressBar::new(done as f32).show_percentage());
+                    }
+                }
+                if let Some((hero, villain)) = &distributions {
+                    ui.label(format!("Hand against villain combos: {hero}"));
+                    distribution_chart(ui, hero);
+                    ui.label(format!("Villain range against hero range: {villain}"));
+                    distribution_chart(ui, villain);
+                }
+                ui.separator();
+                ui.label(emphasized("Session review:", Color32::ORANGE));
+                text_entry(ui, "Hand history:", &mut review_path);
+                if review_thread.as_ref().is_some_and(|t| t.is_finished()) {
+                    reviews = review_thread.take().unwrap().join().unwrap();
+                    review_index = 0;
+                    review_step = 0;
+                }
+                if ui
+                    .add_enabled(
+                        review_thread.is_none() && !review_path.is_empty(),
+                        egui::Button::new("review"),
+                    )
+                    .clicked()
+                {
+                    let path = review_path.clone();
+                    let tracker = tracker.clone();
+                    review_thread = Some(std::thread::spawn(move || {
+                        history::read_histories(path).map(|hands| {
+                            hands
+                                .iter()
+                                .filter_map(|hand| review::review_hand(hand, &tracker))
+                                .collect()
+                        })
+                    }));
+                }
+                if review_thread.is_some() {
+                    ui.spinner();
+                }
+                match &reviews {
+                    Err(e) => {
+                        ui.colored_label(Color32::RED, e);
+                    }
+                    Ok(reviews) if !reviews.is_empty() => {
+                        ui.horizontal(|ui| {
+                            if ui.button("<").clicked() && review_index > 0 {
+                                review_index -= 1;
+                                review_step = 0;
+                            }
+                            ui.label(format!(
+                                "Hand #{} ({}/{})",
+                                reviews[review_index].hand.id,
+                                review_index + 1,
+                                reviews.len()
+                            ));
+                            if ui.button(">").clicked() && review_index + 1 < reviews.len() {
+                                review_index += 1;
+                                review_step = 0;
+                            }
+                        });
+                        let review = &reviews[review_index];
+                        if let Some(hole) = review
+                            .hand
+                            .hole_cards
+                            .iter()
+                            .find(|(p, _)| Some(p) == review.hand.hero.as_ref())
+                        {
+                            let cards = hole
+                                .1
+                                .cards()
+                                .iter()
+                                .map(|c| c.to_string())
+                                .collect::<Vec<_>>();
+                            ui.label(format!("Hole cards: {}", cards.join(" ")));
+                        }
+                        if !review.decisions.is_empty() {
+                            ui.horizontal(|ui| {
+                                if ui.button("previous").clicked() && review_step > 0 {
+                                    review_step -= 1;
+                                }
+                                if ui.button("next").clicked()
+                                    && review_step + 1 < review.decisions.len()
+                                {
+                                    review_step += 1;
+                                }
+                            });
+                            for decision in &review.decisions[..=review_step] {
+                                let color = if decision.deviates() {
+                                    Color32::RED
+                                } else {
+                                    Color32::GREEN
+                                };
+                                ui.colored_label(color, decision.to_string());
+                            }
+                        }
+                        ui.label(emphasized("Biggest leaks:", Color32::ORANGE));
+                        for leak in review::leaks(reviews).iter().take(5) {
+                            ui.label(format!(
+                                "{} ({} times, EV loss {:.0})",
+                                leak.description, leak.count, leak.ev_loss
+                            ));
+                        }
+                    }
+                    Ok(_) => (),
+                }
+            });
+        });
         egui::CentralPanel::default().show(ctx, |ui| {
+            if matchups().is_none()
+                && let Some((done, total)) = *matchups_progress.lock().unwrap()
+            {
+                ui.label(format!(
+                    "Calculating the preflop matchup table: {:.0}%",
+                    done as f64 / total as f64 * 100.0
+                ));
+                ctx.request_repaint_after(std::time::Duration::from_secs(1));
+            }
+            ui.horizontal(|ui| {
+                ui.label("Variant:");
+                for v in Variant::ALL {
+                    ui.selectable_value(&mut variant, v, v.to_string());
+                }
+            });
             text_entry(ui, "Cards in hand:", &mut pocket_cards_input);
             text_entry(ui, "Cards on board:", &mut board_cards_input);
+            text_entry(ui, "Dead cards:", &mut dead_cards_input);
             ui.horizontal(|ui| {
                 ui.label("Opponent variance:");
                 ui.selectable_value(&mut variance, Variance::Normal, "normal");
                 ui.selectable_value(&mut variance, Variance::Random, "random");
             });
+            ui.horizontal(|ui| {
+                ui.label("Villain profile:");
+                for (name, preset) in Profile::PRESETS {
+                    ui.selectable_value(&mut profile, preset, name);
+                }
+                ui.checkbox(&mut use_stats, "tracked");
+            });
+            if variant.omaha() {
+                ui.horizontal(|ui| {
+                    ui.label("Villain plays top:");
+                    ui.add(
+                        egui::Slider::new(&mut omaha_range.top, 0.05..=1.0)
+                            .custom_formatter(|n, _| format!("{:.0}%", n * 100.0)),
+                    );
+                });
+            }
+            text_entry(ui, "Villain name:", &mut villain_name);
+            let villain_stats = tracker.get(&villain_name).copied();
+            if let Some(stats) = villain_stats {
+                ui.label(format!("{} hands: {stats}", stats.hands));
+            }
+            let profile = match villain_stats {
+                Some(stats) if use_stats => Profile::from(&stats),
+                _ => profile,
+            };
             // Parse deck state
-            if let (Some(pocket_cards), Some(board_cards)) = (
+            if let (Some(pocket_cards), Some(board_cards), Some(dead_cards)) = (
                 Vec::parse(&mut pocket_cards_input.chars().filter(|c| !c.is_whitespace())),
                 Vec::parse(&mut board_cards_input.chars().filter(|c| !c.is_whitespace())),
-            ) && pocket_cards.len() == 2
-                && [0, 3, 4, 5].contains(&board_cards.len())
+                Vec::<Card>::parse(&mut dead_cards_input.chars().filter(|c| !c.is_whitespace())),
+            ) && variant.hole_card_counts().contains(&pocket_cards.len())
+                && let Some(hand) = HoleCards::new(&pocket_cards)
+                && let Some(board) = Board::from_cards(&board_cards)
+                && !dead_cards
+                    .iter()
+                    .any(|c| pocket_cards.contains(c) || board_cards.contains(c))
+                && pocket_cards
+                    .iter()
+                    .chain(&board_cards)
+                    .all(|c| variant.deck().contains(c))
             {
                 state = Some(DeckState {
-                    board: match board_cards.as_slice() {
-                        [] => Board::PreFlop,
-                        [a, b, c] => Board::Flop([*a, *b, *c]),
-                        [a, b, c, d] => Board::Turn([*a, *b, *c, *d]),
-                        [a, b, c, d, e] => Board::River([*a, *b, *c, *d, *e]),
-                        _ => unreachable!(),
-                    },
-                    hand: [pocket_cards[0], pocket_cards[1]],
+                    board,
+                    hand,
                     variance,
+                    dead: dead_cards.into_iter().collect(),
+                    variant,
                 });
             } else {
                 ui.colored_label(Color32::RED, "Invalid card inputs");
@@ -126,6 +528,12 @@ fn main() {
                 }
             });
             players_in = players_in.clamp(2, players_at_table);
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut in_position, "in position");
+                ui.label("Left to act:");
+                ui.add(egui::Slider::new(&mut left_to_act, 0..=players_in - 1));
+            });
+            left_to_act = left_to_act.min(players_in - 1);
             ui.separator();
             ui.horizontal(|ui| {
                 ui.label("Chips in pot:");
@@ -167,6 +575,115 @@ fn main() {
             if let Ok(value) = call_price_input.parse::<usize>() {
                 call_price = value;
             }
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Bet sizes:");
+                for (size, enabled) in &mut bet_sizes {
+                    ui.checkbox(enabled, size.to_string());
+                }
+            });
+            ui.horizontal(|ui| {
+                let street = state.map(|s| s.board.street()).unwrap_or_default();
+                ui.label(format!("Villain actions ({street}):"));
+                // The villain matches what the hero has in front of them, or bets or raises to
+                // leave the hero the call price
+                let history = HandHistory::heads_up_actions(
+                    blind,
+                    position == Position::BigBlind,
+                    &villain_name,
+                    &hand_actions,
+                );
+                let hero_in = history
+                    .street_put_in(street)
+                    .get(HERO)
+                    .copied()
+                    .unwrap_or_default();
+                for (name, action) in [
+                    ("fold", Action::Fold),
+                    ("check", Action::Check),
+                    ("call", history.action_to(&villain_name, street, hero_in)),
+                    (
+                        "bet or raise",
+                        history.action_to(&villain_name, street, hero_in + call_price),
+                    ),
+                ] {
+                    if ui.button(name).clicked() {
+                        hand_actions.push(PlayerAction {
+                            player: villain_name.clone(),
+                            street,
+                            action,
+                        });
+                    }
+                }
+                if ui
+                    .add_enabled(!hand_actions.is_empty(), egui::Button::new("clear"))
+                    .clicked()
+                {
+                    hand_actions.clear();
+                }
+                if ui
+                    .add_enabled(!hand_actions.is_empty(), egui::Button::new("log hand"))
+                    .clicked()
+                {
+                    tracker.record_hand(&hand_actions, Some(HERO));
+                    hand_actions.clear();
+                    tracker_error = tracker
+                        .save(storage::data_path("opponents.tsv"))
+                        .err()
+                        .map(|e| format!("Could not save opponent stats: {e}"));
+                }
+                if ui
+                    .add_enabled(
+                        state.is_some() && !hand_actions.is_empty(),
+                        egui::Button::new("save hand"),
+                    )
+                    .clicked()
+                {
+                    let id = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map_or(0, |d| d.as_secs());
+                    let hand = HandHistory::heads_up(
+                        id,
+                        blind,
+                        state.unwrap(),
+                        position == Position::BigBlind,
+                        (
+                            stack,
+                            // Assume an even match when the villain's stack wasn't entered
+                            villain_stack_input.trim().parse().unwrap_or(stack),
+                        ),
+                        &villain_name,
+                        &hand_actions,
+                    );
+                    let path = storage::data_path("hands.txt");
+                    tracker_error = std::fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(&path)
+                        .and_then(|mut file| {
+                            use std::io::Write;
+                            writeln!(file, "{hand}\n")
+                        })
+                        .err()
+                        .map(|e| format!("Could not save hand to {}: {e}", path.display()));
+                }
+            });
+            if !hand_actions.is_empty() {
+                ui.label(
+                    hand_actions
+                        .iter()
+                        .map(|a| format!("{} {} {}", a.player, a.street, a.action))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                );
+            }
+            if let Some(error) = &tracker_error {
+                ui.colored_label(Color32::RED, error);
+            }
+            let villain_actions = hand_actions
+                .iter()
+                .filter(|a| a.player == villain_name)
+                .map(|a| (a.street, a.action))
+                .collect::<Vec<_>>();
             ui.separator();
             ui.horizontal(|ui| {
                 ui.label("Chips in stack:");
@@ -206,17 +723,39 @@ fn main() {
                     stack_input = "0".to_string();
                 }
             });
+            text_entry(ui, "Villain stack:", &mut villain_stack_input);
+            let effective_stack = match villain_stack_input.trim().parse::<usize>() {
+                Ok(villain_stack) => stack.min(villain_stack),
+                Err(_) => stack,
+            };
             ui.separator();
+            let inputs_changed = last_state != state
+                || last_villain != (villain_actions.clone(), profile, omaha_range);
+            if strength_calc_thread.is_some() && inputs_changed {
+                // The hand being calculated is no longer the one entered
+                strength_cancel.cancel();
+                strength_calc_thread = None;
+                hand_strength = None;
+            }
             if strength_calc_thread
                 .as_ref()
                 .is_some_and(|t| t.is_finished())
             {
-                hand_strength = Some(strength_calc_thread.take().unwrap().join().unwrap());
+                if let Some((strength, hi_lo)) =
+                    strength_calc_thread.take().unwrap().join().unwrap()
+                {
+                    hand_strength = Some(strength);
+                    hi_lo_equity = hi_lo;
+                }
                 strength_calc_thread = None;
+            } else if strength_calc_thread.is_some()
+                && let Some(progress) = *strength_progress.lock().unwrap()
+            {
+                hand_strength = Some(progress.strength);
             }
             if ui
                 .add_enabled(
-                    strength_calc_thread.is_none() && state.is_some() && last_state != state,
+                    strength_calc_thread.is_none() && state.is_some() && inputs_changed,
                     egui::Button::new(if last_state.is_none() {
                         "calculate hand"
                     } else {
@@ -226,9 +765,57 @@ fn main() {
                 .clicked()
             {
                 last_state = state;
+                last_villain = (villain_actions.clone(), profile, omaha_range);
                 hand_strength = None;
-                strength_calc_thread =
-                    Some(std::thread::spawn(move || last_state.unwrap().strength()));
+                hi_lo_equity = None;
+                let mut range = match villain_stats {
+                    Some(stats) if use_stats => {
+                        Range::from(HandChart::opponent_expectation_for(&stats))
+                    }
+                    _ => state.unwrap().opponent_range(),
+                };
+                range = range.without(variant.missing_cards());
+                range.narrow(&profile, state.unwrap().board, variant, &villain_actions);
+                fold_model = Some(range.fold_model(&profile, state.unwrap().board, variant));
+                blocker_report = Some(range.blockers(
+                    state.unwrap().hand.cards(),
+                    state.unwrap().board,
+                    variant,
+                ));
+                composition = Some(range.composition(state.unwrap().board));
+                villain_range = Some(range.clone());
+                strength_cancel = CancelToken::default();
+                strength_progress = Default::default();
+                let cancel = strength_cancel.clone();
+                let shared_progress = strength_progress.clone();
+                let opponent_range = if variant.omaha() {
+                    OpponentRange::Omaha(omaha_range)
+                } else {
+                    OpponentRange::Combos(range)
+                };
+                let ctx = ctx.clone();
+                strength_calc_thread = Some(std::thread::spawn(move || {
+                    let state = last_state.unwrap();
+                    let progress = |progress| {
+                        *shared_progress.lock().unwrap() = Some(progress);
+                        ctx.request_repaint();
+                    };
+                    let result = if state.variant.hi_lo() {
+                        state
+                            .sampled_hi_lo_equity(&omaha_range, OMAHA_SAMPLES, &cancel, &progress)
+                            .map(|equity| (equity.total(), Some(equity)))
+                    } else {
+                        state
+                            .cancellable_strength_against(&opponent_range, &cancel, &progress)
+                            .map(|strength| (strength, None))
+                    };
+                    ctx.request_repaint();
+                    result
+                }));
+            }
+            if strength_calc_thread.is_some() {
+                let done = strength_progress.lock().unwrap().map_or(0.0, |p| p.done);
+                ui.add(egui::ProgressBar::new(done as f32).show_percentage());
             }
             if let (Some(mut hand_strength), Some(state)) = (hand_strength, last_state) {
                 ui.label(
@@ -246,15 +833,15 @@ fn main() {
                 );
                 hand_strength = hand_strength.powi(players_in as i32 - 1);
                 let pot_odds = call_price as f64 / (pot + call_price) as f64;
-                let expected_value =
-                    (pot as f64 * hand_strength) - (call_price as f64 * (1.0 - hand_strength));
                 let opponent_strength = (1.0 - hand_strength) / (players_in - 1) as f64;
-                if state.board == Board::PreFlop {
+                if state.board == Board::PreFlop
+                    && let Some(hand) = state.hand.pair()
+                {
                     if position != Position::BigBlind {
                         ui.horizontal(|ui| {
                             ui.label("Opening move:");
                             let range = position.gto_preflop();
-                            let recommendation = match range[state.hand] {
+                            let recommendation = match range[hand] {
                                 Recommendation::Fold => "Fold".to_string(),
                                 Recommendation::Call => "Call BB".to_string(),
                                 Recommendation::Raise => {
@@ -275,22 +862,63 @@ fn main() {
                         });
                     }
                 }
-                let (rec, amount) = ai::decide(
-                    position,
+                let menu = bet_sizes
+                    .iter()
+                    .filter(|(_, enabled)| *enabled)
+                    .map(|(size, _)| *size)
+                    .collect::<Vec<_>>();
+                // Chips in front of the hero on this street, which hero actions add to
+                let street = state.board.street();
+                let history = HandHistory::heads_up_actions(
+                    blind,
+                    position == Position::BigBlind,
+                    &villain_name,
+                    &hand_actions,
+                );
+                let hero_in = history
+                    .street_put_in(street)
+                    .get(HERO)
+                    .copied()
+                    .unwrap_or_default();
+                let spot = ai::Spot {
+                    hand: state.hand,
+                    board: state.board,
                     hand_strength,
-                    pot_odds,
-                    players_at_table - 1,
                     call_price,
+                    put_in: hero_in,
                     pot,
                     stack,
+                    effective_stack,
                     blind,
-                );
+                    in_position,
+                    left_to_act,
+                    opponents: fold_model
+                        .clone()
+                        .map(|model| vec![model; players_in - 1])
+                        .unwrap_or_default(),
+                    variant: state.variant,
+                };
+                let advice = ai::decide(&spot, &menu);
+                let odds = spot.implied_odds();
+                let expected_value =
+                    ai::call_ev(hand_strength, call_price, pot) + odds.implied - odds.reverse;
+                let (rec, amount) = (advice.recommendation, advice.amount);
+                let adding = |chips| history.action_to(HERO, street, hero_in + chips);
+                let (call, raise, all_in) = (adding(call_price), adding(amount), adding(stack));
+                let mut hero_action = |action| {
+                    hand_actions.push(PlayerAction {
+                        player: HERO.to_string(),
+                        street,
+                        action,
+                    })
+                };
                 match rec {
                     Recommendation::Fold => {
                         if ui
                             .button(emphasized("Fold at this call price", Color32::RED))
                             .clicked()
                         {
+                            hero_action(Action::Fold);
                             pocket_cards_input.clear();
                             board_cards_input.clear();
                             pot = 0;
@@ -301,6 +929,7 @@ fn main() {
                             .button(emphasized("Call at this price", Color32::GREEN))
                             .clicked()
                         {
+                            hero_action(call);
                             call_price_input.clear();
                             pot += call_price;
                             stack -= call_price;
@@ -311,6 +940,7 @@ fn main() {
                             .button(emphasized(format!("Raise {amount}"), Color32::ORANGE))
                             .clicked()
                         {
+                            hero_action(raise);
                             call_price_input.clear();
                             pot += amount;
                             stack -= amount;
@@ -318,6 +948,7 @@ fn main() {
                     }
                     Recommendation::AllIn => {
                         if ui.label(emphasized("All-in", Color32::PURPLE)).clicked() {
+                            hero_action(all_in);
                             call_price_input.clear();
                             pot += stack;
                             stack = 0;
@@ -334,6 +965,28 @@ fn main() {
                             .underline(),
                     );
                 });
+                if state.board != Board::PreFlop {
+                    let rank = state.current_rank();
+                    let class = rank.class().map(|class| format!(" (class {class})"));
+                    ui.horizontal(|ui| {
+                        ui.label("Made hand:");
+                        ui.label(
+                            RichText::new(format!("{rank}{}", class.unwrap_or_default()))
+                                .color(Color32::GREEN)
+                                .underline(),
+                        );
+                    });
+                }
+                if let Some(equity) = hi_lo_equity {
+                    ui.horizontal(|ui| {
+                        ui.label("Split pot:");
+                        ui.label(
+                            RichText::new(equity.to_string())
+                                .color(Color32::GREEN)
+                                .underline(),
+                        );
+                    });
+                }
                 ui.horizontal(|ui| {
                     ui.label("Pot odds:");
                     ui.label(
@@ -351,6 +1004,42 @@ fn main() {
                             .underline(),
                     ),
                 );
+                labelled(
+                    ui,
+                    "Implied odds:",
+                    egui::Label::new(
+                        RichText::new(format!("{:+.0}", odds.implied))
+                            .color(Color32::GREEN)
+                            .underline(),
+                    ),
+                );
+                labelled(
+                    ui,
+                    "Reverse implied odds:",
+                    egui::Label::new(
+                        RichText::new(format!("{:+.0}", -odds.reverse))
+                            .color(Color32::RED)
+                            .underline(),
+                    ),
+                );
+                labelled(
+                    ui,
+                    "Stack to pot ratio:",
+                    egui::Label::new(
+                        RichText::new(format!("{:.1}", spot.spr()))
+                            .color(Color32::GREEN)
+                            .underline(),
+                    ),
+                );
+                labelled(
+                    ui,
+                    "Plan:",
+                    egui::Label::new(
+                        RichText::new(spot.plan().to_string())
+                            .color(Color32::GREEN)
+                            .underline(),
+                    ),
+                );
                 labelled(
                     ui,
                     "Opponent strength:",
@@ -360,8 +1049,18 @@ fn main() {
                             .underline(),
                     ),
                 );
-            } else if strength_calc_thread.is_some() {
-                ui.spinner();
+                ui.label(emphasized("Options:", Color32::ORANGE));
+                for choice in &advice.choices {
+                    if (choice.recommendation, choice.amount) == (rec, amount) {
+                        ui.label(emphasized(choice, Color32::GREEN));
+                    } else {
+                        ui.label(choice.to_string());
+                    }
+                }
+                if let Some(balance) = &advice.balance {
+                    ui.label(emphasized("Balanced river strategy:", Color32::ORANGE));
+                    ui.label(balance.to_string());
+                }
             }
         });
     })
@@ -0,0 +1,305 @@
+use std::{io, path::Path};
+
+use rayon::prelude::*;
+
+use crate::{
+    cards::*,
+    eval::Recommendation,
+    iso::{HandIndexer, distinct_boards, indexer},
+    parse::Parse,
+    preflop::*,
+    range::*,
+};
+
+/// Number of deals of two hands, counting deals that only differ by exchanging suits once
+const PATTERNS: usize = 93_769;
+
+/// Boards calculated between progress reports
+const BOARDS_PER_REPORT: usize = 1000;
+
+/// Cards of a hand as bits of `Card::index`
+fn mask(hand: [Card; 2]) -> u64 {
+    hand.iter().fold(0, |mask, card| mask | 1 << card.index())
+}
+
+/// Indexer of deals of two hands, the same for every deal that only differs by exchanging suits
+fn pattern_indexer() -> std::sync::Arc<HandIndexer> {
+    indexer(&[2, 2])
+}
+
+/// Patterns of every pair of `combos` that don't share a card, with one hand and then the
+/// other first. The pair of `combos[i]` and `combos[j]` for `i < j` is at
+/// `j * (j - 1) / 2 + i`.
+fn pair_patterns(combos: &[[Card; 2]]) -> Vec<Option<(u32, u32)>> {
+    let indexer = pattern_indexer();
+    (1..combos.len())
+        .into_par_iter()
+        .flat_map_iter(|j| {
+            let indexer = indexer.clone();
+            (0..j).map(move |i| {
+                let (hand, other) = (combos[i], combos[j]);
+                (mask(hand) & mask(other) == 0).then(|| {
+                    (
+                        indexer.index(&[&hand, &other]) as u32,
+                        indexer.index(&[&other, &hand]) as u32,
+                    )
+                })
+            })
+        })
+        .collect()
+}
+
+/// Pots won by the first hand of each pattern of two hands from `combos`, and pots played, on
+/// `boards` weighted by the number of boards each stands for. Every pair of hands of a pattern
+/// is played, so that the boards a board stands for, which only differ by exchanging suits,
+/// count the same for the pattern as a whole.
+fn tally(
+    boards: &[(Vec<Card>, usize)],
+    combos: &[[Card; 2]],
+    patterns: &[Option<(u32, u32)>],
+) -> (Vec<f64>, Vec<f64>) {
+    boards
+        .par_iter()
+        .fold(
+            || (vec![0.0; PATTERNS], vec![0.0; PATTERNS]),
+            |(mut won, mut played), (board, count)| {
+                let board_mask = board.iter().fold(0u64, |m, c| m | 1 << c.index());
+                let live = combos
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, hand)| mask(**hand) & board_mask == 0)
+                    .map(|(i, hand)| {
+                        let rank = Variant::Holdem.best_rank_in(hand.iter().chain(board).copied());
+                        (i, rank)
+                    })
+                    .collect::<Vec<_>>();
+                let count = *count as f64;
+                for (n, (i, rank)) in live.iter().enumerate() {
+                    for (j, other_rank) in &live[n + 1..] {
+                        let Some((forward, backward)) = patterns[j * (j - 1) / 2 + i] else {
+                            continue;
+                        };
+                        let share = match rank.cmp(other_rank) {
+                            std::cmp::Ordering::Greater => 1.0,
+                            std::cmp::Ordering::Equal => 0.5,
+                            std::cmp::Ordering::Less => 0.0,
+                        };
+                        won[forward as usize] += share * count;
+                        won[backward as usize] += (1.0 - share) * count;
+                        played[forward as usize] += count;
+                        played[backward as usize] += count;
+                    }
+                }
+                (won, played)
+            },
+        )
+        .reduce(
+            || (vec![0.0; PATTERNS], vec![0.0; PATTERNS]),
+            |(mut won, mut played), (other_won, other_played)| {
+                for i in 0..PATTERNS {
+                    won[i] += other_won[i];
+                    played[i] += other_played[i];
+                }
+                (won, played)
+            },
+        )
+}
+
+/// Preflop all-in equity of every hand against every other hand that doesn't share a card,
+/// kept once for each pattern of suits, as hands that only differ by exchanging suits have the
+/// same equity
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matchups(Vec<f64>);
+
+impl Matchups {
+    /// Exact equities from every five card board. Boards that only differ by exchanging suits
+    /// are visited once for all of them. Calls `progress` with the number of boards done and
+    /// the total along the way. Takes about an hour on a single core.
+    pub fn calculate(mut progress: impl FnMut(usize, usize)) -> Self {
+        let combos = all_combos().collect::<Vec<_>>();
+        let patterns = pair_patterns(&combos);
+        let boards = distinct_boards(&deck(), &[], 5);
+        let mut won = vec![0.0; PATTERNS];
+        let mut played = vec![0.0; PATTERNS];
+        for (chunk_index, chunk) in boards.chunks(BOARDS_PER_REPORT).enumerate() {
+            let (chunk_won, chunk_played) = tally(chunk, &combos, &patterns);
+            for i in 0..PATTERNS {
+                won[i] += chunk_won[i];
+                played[i] += chunk_played[i];
+            }
+            progress(
+                (chunk_index * BOARDS_PER_REPORT + chunk.len()).min(boards.len()),
+                boards.len(),
+            );
+        }
+        Matchups(won.iter().zip(&played).map(|(w, p)| w / p).collect())
+    }
+
+    /// Equities saved one pattern per line: a hand, a hand against it and the first hand's
+    /// equity, separated by tabs
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let invalid = |line: usize| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid matchup equities on line {}", line + 1),
+            )
+        };
+        let indexer = pattern_indexer();
+        let mut equities = vec![None; PATTERNS];
+        for (line_number, line) in text.lines().enumerate() {
+            let [hand, other, equity] = line
+                .split('\t')
+                .collect::<Vec<_>>()
+                .try_into()
+                .map_err(|_| invalid(line_number))?;
+            let parse = |text: &str| {
+                let chars = &mut text.chars();
+                Card::parse_n::<2>(chars)
+                    .filter(|hand| hand[0] != hand[1] && chars.next().is_none())
+            };
+            let (Some(hand), Some(other), Ok(equity)) = (parse(hand), parse(other), equity.parse())
+            else {
+                return Err(invalid(line_number));
+            };
+            if mask(hand) & mask(other) != 0 {
+                return Err(invalid(line_number));
+            }
+            equities[indexer.index(&[&hand, &other])] = Some(equity);
+        }
+        equities
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .filter(|_| text.lines().count() == PATTERNS)
+            .map(Matchups)
+            .ok_or_else(|| invalid(text.lines().count()))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        // The first pair of hands of each pattern stands for it
+        let indexer = pattern_indexer();
+        let mut lines = vec![None; PATTERNS];
+        for hand in all_combos() {
+            for other in all_combos().filter(|other| mask(*other) & mask(hand) == 0) {
+                let pattern = indexer.index(&[&hand, &other]);
+                lines[pattern].get_or_insert_with(|| {
+                    format!(
+                        "{}{}\t{}{}\t{}\n",
+                        hand[0], hand[1], other[0], other[1], self.0[pattern]
+                    )
+                });
+            }
+        }
+        std::fs::write(path, lines.into_iter().flatten().collect::<String>())
+    }
+
+    /// Weighted pots won against the combinations of `villain` that don't share a card with
+    /// `hand`, and their total weight
+    fn against(&self, indexer: &HandIndexer, hand: [Card; 2], villain: &Range) -> (f64, f64) {
+        villain
+            .combos()
+            .filter(|(other, _)| mask(*other) & mask(hand) == 0)
+            .fold((0.0, 0.0), |(won, total), (other, weight)| {
+                let equity = self.0[indexer.index(&[&hand, &other])];
+                (won + equity * weight, total + weight)
+            })
+    }
+
+    /// All-in equity of `hand` against a weighted range. An empty range gives even odds.
+    pub fn hand_equity(&self, hand: [Card; 2], villain: &Range) -> f64 {
+        let (won, total) = self.against(&pattern_indexer(), hand, villain);
+        if total == 0.0 { 0.5 } else { won / total }
+    }
+
+    /// All-in equity of one weighted range against another, counting only pairs of
+    /// combinations that don't share a card
+    pub fn range_equity(&self, hero: &Range, villain: &Range) -> f64 {
+        let indexer = pattern_indexer();
+        let (won, total) = hero
+            .combos()
+            .collect::<Vec<_>>()
+            .par_iter()
+            .map(|(hand, weight)| {
+                let (hand_won, hand_total) = self.against(&indexer, *hand, villain);
+                (hand_won * weight, hand_total * weight)
+            })
+            .reduce(|| (0.0, 0.0), |a, b| (a.0 + b.0, a.1 + b.1));
+        if total == 0.0 { 0.5 } else { won / total }
+    }
+
+    /// Hands worth moving all in for `stack` big blinds from the small blind when the big
+    /// blind calls with `calling` and folds everything else. Each class counts the average
+    /// over its combinations.
+    pub fn shoving_chart(&self, stack: f64, calling: &Range) -> HandChart {
+        let indexer = pattern_indexer();
+        let classes = unique_open_hands()
+            .into_par_iter()
+            .map(|class| {
+                let combos = all_combos()
+                    .filter(|hand| {
+                        index(OpeningHand::from(*hand)) == index(OpeningHand::from(class))
+                    })
+                    .collect::<Vec<_>>();
+                // Big blinds won compared with folding the small blind
+                let shove = combos
+                    .iter()
+                    .map(|hand| {
+                        let (won, called) = self.against(&indexer, *hand, calling);
+                        let all = Range::full().without(*hand).combos().count() as f64;
+                        let call_chance = called / all;
+                        let equity = if called == 0.0 { 0.5 } else { won / called };
+                        (1.0 - call_chance) * 1.5
+                            + call_chance * (equity * 2.0 * stack - stack + 0.5)
+                    })
+                    .sum::<f64>()
+                    / combos.len() as f64;
+                (class, shove)
+            })
+            .collect::<Vec<_>>();
+        let mut chart = HandChart::default();
+        for (class, shove) in classes {
+            if shove > 0.0 {
+                chart[class] = Recommendation::AllIn;
+            }
+        }
+        chart
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    /// Counting each distinct board once for all the boards it stands for gives every pattern
+    /// of hands the same pots as going through every board, here for the cards from queens up
+    #[test]
+    fn patterns_match_every_board() {
+        let deck = deck()
+            .into_iter()
+            .filter(|c| c.0.high() >= Face::Queen.high())
+            .collect::<Vec<_>>();
+        let combos = deck
+            .iter()
+            .copied()
+            .combinations(2)
+            .map(|hand| [hand[0], hand[1]])
+            .collect::<Vec<_>>();
+        let patterns = pair_patterns(&combos);
+        let boards = distinct_boards(&deck, &[], 5);
+        assert!(boards.len() * 10 < 792);
+        let (won, played) = tally(&boards, &combos, &patterns);
+
+        let every_board = deck
+            .iter()
+            .copied()
+            .combinations(5)
+            .map(|board| (board, 1))
+            .collect::<Vec<_>>();
+        let (every_won, every_played) = tally(&every_board, &combos, &patterns);
+        assert_eq!(played, every_played);
+        assert_eq!(won, every_won);
+        assert!(played.iter().filter(|p| **p > 0.0).count() > 100);
+    }
+}
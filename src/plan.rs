@@ -0,0 +1,277 @@
+use crate::{ai::Spot, cards::*, draws::Draws, rank::*, state::*};
+
+/// At or below this stack-to-pot ratio, top pair is worth getting all in with
+pub const COMMIT_SPR: f64 = 3.0;
+/// At or above this stack-to-pot ratio, one pair should keep the pot small
+pub const POT_CONTROL_SPR: f64 = 6.0;
+
+/// Effective stack behind divided by the pot
+pub fn spr(effective_stack: usize, pot: usize) -> f64 {
+    if pot == 0 {
+        return f64::INFINITY;
+    }
+    effective_stack as f64 / pot as f64
+}
+
+/// How strong the made hand using the hero's hole cards is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MadeHand {
+    Nothing,
+    /// A pair below the top card of the board
+    WeakPair,
+    /// Top pair or an overpair
+    TopPair,
+    /// Two pairs or better
+    Strong,
+}
+
+impl MadeHand {
+    pub fn find(variant: Variant, hand: &[Card], board: &[Card]) -> Self {
+        if board.len() < 3 {
+            return MadeHand::Nothing;
+        }
+        let pocket_pair = hand
+            .iter()
+            .enumerate()
+            .filter(|(i, a)| hand[i + 1..].iter().any(|b| b.0 == a.0))
+            .map(|(_, a)| a.0.high())
+            .max();
+        let paired = hand
+            .iter()
+            .filter(|h| board.iter().any(|b| b.0 == h.0))
+            .map(|h| h.0.high())
+            .max();
+        let kind = variant.best_rank_for(hand, board).kind();
+        let top = board.iter().map(|c| c.0.high()).max().unwrap_or_default();
+        if kind >= RankKind::Straight || (kind >= RankKind::TwoPairs && paired.is_some()) {
+            MadeHand::Strong
+        } else if pocket_pair.is_some_and(|pair| pair > top) || paired == Some(top) {
+            MadeHand::TopPair
+        } else if pocket_pair.is_some() || paired.is_some() {
+            MadeHand::WeakPair
+        } else {
+            MadeHand::Nothing
+        }
+    }
+}
+
+/// How the stack-to-pot ratio ties the hero to the pot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Commitment {
+    /// Get all in rather than fold
+    Committed,
+    Flexible,
+    /// Keep the pot small to avoid stacking off with one pair
+    PotControl,
+}
+
+impl std::fmt::Display for Commitment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Commitment::Committed => "committed",
+                Commitment::Flexible => "flexible",
+                Commitment::PotControl => "pot control",
+            }
+        )
+    }
+}
+
+/// The action planned for one street
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StreetPlan {
+    /// Bet a fraction of the pot
+    Bet(f64),
+    Shove,
+    /// Check and call a bet
+    CheckCall,
+    /// Check and fold to a bet
+    CheckFold,
+}
+
+impl std::fmt::Display for StreetPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreetPlan::Bet(fraction) => write!(f, "bet {:.0}%", fraction * 100.0),
+            StreetPlan::Shove => write!(f, "shove"),
+            StreetPlan::CheckCall => write!(f, "check / call"),
+            StreetPlan::CheckFold => write!(f, "check / fold"),
+        }
+    }
+}
+
+/// Actions for the current and later streets, assuming every bet is called
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub made: MadeHand,
+    pub commitment: Commitment,
+    /// Each street with the stack-to-pot ratio at its start and the planned action
+    pub streets: Vec<(Street, f64, StreetPlan)>,
+}
+
+impl std::fmt::Display for Plan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let streets = self
+            .streets
+            .iter()
+            .map(|(street, spr, plan)| format!("{plan} {street} (SPR {spr:.1})"))
+            .collect::<Vec<_>>();
+        write!(f, "{}: {}", self.commitment, streets.join(", "))
+    }
+}
+
+impl Spot {
+    /// Stack-to-pot ratio once the hero has called
+    pub fn spr(&self) -> f64 {
+        spr(
+            self.effective_stack.saturating_sub(self.call_price),
+            self.pot + 2 * self.call_price,
+        )
+    }
+
+    pub fn commitment(&self) -> Commitment {
+        let board = self.board.into_iter().collect::<Vec<_>>();
+        let made = MadeHand::find(self.variant, self.hand.cards(), &board);
+        let spr = self.spr();
+        if spr <= COMMIT_SPR && made >= MadeHand::TopPair {
+            Commitment::Committed
+        } else if spr >= POT_CONTROL_SPR && (MadeHand::WeakPair..=MadeHand::TopPair).contains(&made)
+        {
+            Commitment::PotControl
+        } else {
+            Commitment::Flexible
+        }
+    }
+
+    /// Plan the remaining streets from the strength of the made hand and the stack-to-pot
+    /// ratio. Committed hands bet a size that gets the stacks in by the river.
+    pub fn plan(&self) -> Plan {
+        let board = self.board.into_iter().collect::<Vec<_>>();
+        let made = MadeHand::find(self.variant, self.hand.cards(), &board);
+        let draws = self
+            .hand
+            .pair()
+            .map(|hand| Draws::find(self.variant, hand, &board))
+            .unwrap_or_default();
+        let commitment = self.commitment();
+        let streets = Street::ALL
+            .into_iter()
+            .filter(|s| {
+                *s > self.board.street() || (*s == self.board.street() && *s != Street::PreFlop)
+            })
+            .collect::<Vec<_>>();
+        let mut stack = self.effective_stack.saturating_sub(self.call_price) as f64;
+        let mut pot = (self.pot + 2 * self.call_price) as f64;
+        let mut plan = vec![];
+        for (i, street) in streets.iter().copied().enumerate() {
+            let spr = if pot > 0.0 {
+                stack / pot
+            } else {
+                f64::INFINITY
+            };
+            let left = (streets.len() - i) as i32;
+            let action = match (commitment, made) {
+                _ if stack <= 0.0 => StreetPlan::CheckCall,
+                (Commitment::Committed, _) => {
+                    // The same fraction on every street puts the stacks in on the river
+                    let fraction = ((2.0 * spr + 1.0).powf(1.0 / left as f64) - 1.0) / 2.0;
+                    StreetPlan::Bet(fraction)
+                }
+                (Commitment::PotControl, _) if i == 0 => StreetPlan::Bet(0.33),
+                (Commitment::PotControl, _) => StreetPlan::CheckCall,
+                (_, MadeHand::Strong | MadeHand::TopPair) => {
+                    StreetPlan::Bet([0.5, 0.66, 0.75][(3 - left).clamp(0, 2) as usize])
+                }
+                (_, MadeHand::WeakPair) => StreetPlan::CheckCall,
+                (_, MadeHand::Nothing) if i == 0 && (draws.flush_draw || draws.open_ended) => {
+                    StreetPlan::Bet(0.5)
+                }
+                (_, MadeHand::Nothing) => StreetPlan::CheckFold,
+            };
+            let action = match action {
+                StreetPlan::Bet(fraction) if fraction * pot >= stack * 0.8 => StreetPlan::Shove,
+                action => action,
+            };
+            let bet = match action {
+                StreetPlan::Bet(fraction) => fraction * pot,
+                StreetPlan::Shove => stack,
+                _ => 0.0,
+            };
+            stack -= bet;
+            pot += 2.0 * bet;
+            plan.push((street, spr, action));
+        }
+        Plan {
+            made,
+            commitment,
+            streets: plan,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::{HoleCards, Variant};
+
+    fn spot(hand: [Card; 2], board: &[Card], effective_stack: usize) -> Spot {
+        Spot {
+            hand: HoleCards::from(hand),
+            board: Board::from_cards(board).unwrap(),
+            hand_strength: 0.5,
+            call_price: 0,
+            put_in: 0,
+            pot: 100,
+            stack: effective_stack,
+            effective_stack,
+            blind: 2,
+            in_position: true,
+            left_to_act: 0,
+            opponents: vec![],
+            variant: Variant::Holdem,
+        }
+    }
+
+    /// Top pair gets the stacks in at a low stack-to-pot ratio and controls the pot at a
+    /// high one
+    #[test]
+    fn commitment_plans() {
+        let board = cards!("Ks", "7d", "2c");
+        let shallow = spot(cards!("Ah", "Kh"), &board, 250);
+        assert_eq!(shallow.spr(), 2.5);
+        let plan = shallow.plan();
+        assert_eq!(plan.made, MadeHand::TopPair);
+        assert_eq!(plan.commitment, Commitment::Committed);
+        assert_eq!(
+            plan.streets.iter().map(|s| s.0).collect::<Vec<_>>(),
+            [Street::Flop, Street::Turn, Street::River]
+        );
+        assert!(plan.streets.iter().any(|s| s.2 == StreetPlan::Shove));
+
+        let deep = spot(cards!("Ah", "Kh"), &board, 1000);
+        let plan = deep.plan();
+        assert_eq!(plan.commitment, Commitment::PotControl);
+        assert_eq!(
+            plan.streets.iter().map(|s| s.2).collect::<Vec<_>>(),
+            [
+                StreetPlan::Bet(0.33),
+                StreetPlan::CheckCall,
+                StreetPlan::CheckCall
+            ]
+        );
+
+        assert_eq!(
+            MadeHand::find(Variant::Holdem, &cards!("7h", "7c"), &board),
+            MadeHand::Strong
+        );
+        assert_eq!(
+            MadeHand::find(Variant::Holdem, &cards!("Ah", "2h"), &board),
+            MadeHand::WeakPair
+        );
+        let nothing = spot(cards!("9h", "8h"), &board, 1000).plan();
+        assert_eq!(nothing.made, MadeHand::Nothing);
+        assert_eq!(nothing.streets[0].2, StreetPlan::CheckFold);
+    }
+}
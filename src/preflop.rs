@@ -1,4 +1,4 @@
-use crate::{cards::*, eval::Recommendation, parse::Parse, state::Position};
+use crate::{cards::*, eval::Recommendation, parse::Parse, state::Position, stats::OpponentStats};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HandSuite {
@@ -42,6 +42,53 @@ impl From<[Card; 2]> for OpeningHand {
     }
 }
 
+impl OpeningHand {
+    /// Hand class at a cell of a `HandChart`
+    pub fn at(x: usize, y: usize) -> Self {
+        let face = |i: usize| Face::ALL[12 - i];
+        if x < y {
+            OpeningHand([face(x), face(y)], HandSuite::Suited)
+        } else {
+            OpeningHand([face(y), face(x)], HandSuite::OffSuite)
+        }
+    }
+
+    /// Strength of a starting hand according to the Chen formula
+    pub fn chen_score(&self) -> f64 {
+        let points = |f: Face| match f {
+            Face::Ace => 10.0,
+            Face::King => 8.0,
+            Face::Queen => 7.0,
+            Face::Jack => 6.0,
+            f => f as usize as f64 / 2.0,
+        };
+        let (high, low) = if self.0[0].high() >= self.0[1].high() {
+            (self.0[0], self.0[1])
+        } else {
+            (self.0[1], self.0[0])
+        };
+        if high == low {
+            return (points(high) * 2.0).max(5.0);
+        }
+        let mut score = points(high);
+        if self.1 == HandSuite::Suited {
+            score += 2.0;
+        }
+        let gap = high.high() - low.high() - 1;
+        score -= match gap {
+            0 => 0.0,
+            1 => 1.0,
+            2 => 2.0,
+            3 => 4.0,
+            _ => 5.0,
+        };
+        if gap <= 1 && high.high() < Face::Queen.high() {
+            score += 1.0;
+        }
+        score.ceil()
+    }
+}
+
 impl Parse for OpeningHand {
     fn parse(iter: &mut impl Iterator<Item = char>) -> Option<Self> {
         let faces = Face::parse_n(iter)?;
@@ -83,6 +130,39 @@ impl HandChart {
         )
     }
 
+    /// Opening range of an opponent based on how many hands they have been seen to play
+    pub fn opponent_expectation_for(stats: &OpponentStats) -> Self {
+        match stats.vpip() {
+            Some(vpip) if stats.is_reliable() => Self::top_fraction(Recommendation::Call, vpip),
+            _ => Self::opponent_expectation(),
+        }
+    }
+
+    /// The strongest hand classes by Chen score, covering `fraction` of all combinations
+    pub fn top_fraction(rec: Recommendation, fraction: f64) -> Self {
+        let mut classes = (0..13)
+            .flat_map(|x| (0..13).map(move |y| (x, y)))
+            .collect::<Vec<_>>();
+        classes.sort_by(|a, b| {
+            let score = |(x, y)| OpeningHand::at(x, y).chen_score();
+            score(*b).total_cmp(&score(*a))
+        });
+        let mut this = Self::default();
+        let mut combos = 0;
+        for (x, y) in classes {
+            if combos as f64 >= fraction * 1326.0 {
+                break;
+            }
+            this[(x, y)] = rec;
+            combos += match x.cmp(&y) {
+                std::cmp::Ordering::Less => 4,
+                std::cmp::Ordering::Equal => 6,
+                std::cmp::Ordering::Greater => 12,
+            };
+        }
+        this
+    }
+
     pub fn filter_hand(&self, to_filter: impl Into<OpeningHand>) -> bool {
         self[to_filter.into()] > Recommendation::Fold
     }
@@ -140,8 +220,8 @@ impl HandChart {
     }
 }
 
-fn index(idx: OpeningHand) -> (usize, usize) {
-    let normalize = |f: Face| 12 - (if f == Face::Ace { 14 } else { f as usize } - 2);
+pub fn index(idx: OpeningHand) -> (usize, usize) {
+    let normalize = |f: Face| 12 - (f.high() - 2);
     let mut arr = idx.0.map(normalize);
     arr.sort();
     let [f1, f2] = arr;
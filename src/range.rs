@@ -0,0 +1,589 @@
+use itertools::Itertools;
+use rand::seq::IndexedRandom;
+
+use crate::{cards::*, draws::Draws, eval::Recommendation, preflop::*, rank::*, state::*};
+
+fn combo_index(hand: [Card; 2]) -> usize {
+    let (a, b) = (hand[0].index(), hand[1].index());
+    let (a, b) = (a.min(b), a.max(b));
+    b * (b - 1) / 2 + a
+}
+
+/// Every pair of hole cards, in the order used to index a `Range`
+pub fn all_combos() -> impl Iterator<Item = [Card; 2]> {
+    (1..52).flat_map(|b| (0..b).map(move |a| [Card::from_index(a), Card::from_index(b)]))
+}
+
+/// Weighted set of hole card combinations an opponent may hold
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range(Vec<f64>);
+
+impl From<HandChart> for Range {
+    fn from(chart: HandChart) -> Self {
+        Range(
+            all_combos()
+                .map(|hand| if chart.filter_hand(hand) { 1.0 } else { 0.0 })
+                .collect(),
+        )
+    }
+}
+
+/// A range holding only `hand`
+impl From<[Card; 2]> for Range {
+    fn from(hand: [Card; 2]) -> Self {
+        let mut range = Range(vec![0.0; all_combos().count()]);
+        range.set_weight(hand, 1.0);
+        range
+    }
+}
+
+impl Range {
+    /// Uniform range over every combination
+    pub fn full() -> Self {
+        Range::from(HandChart::filled_with(Recommendation::Call))
+    }
+
+    pub fn set_weight(&mut self, hand: [Card; 2], weight: f64) {
+        self.0[combo_index(hand)] = weight;
+    }
+
+    /// Combinations with a non-zero weight
+    pub fn combos(&self) -> impl Iterator<Item = ([Card; 2], f64)> + '_ {
+        all_combos()
+            .zip(self.0.iter().copied())
+            .filter(|(_, weight)| *weight > 0.0)
+    }
+
+    /// The range with every suit exchanged for `permutation[suit]`
+    pub fn permuted(&self, permutation: [Suite; 4]) -> Self {
+        let mut permuted = Range(vec![0.0; self.0.len()]);
+        let exchange = |c: Card| Card(c.0, permutation[c.1 as usize - 1]);
+        for (hand, weight) in self.combos() {
+            permuted.set_weight(hand.map(exchange), weight);
+        }
+        permuted
+    }
+
+    /// Hash of the weights that stays the same between runs
+    pub fn fingerprint(&self) -> u64 {
+        // FNV-1a over the bits of every weight
+        self.0.iter().fold(0xcbf29ce484222325, |hash, weight| {
+            weight
+                .to_bits()
+                .to_le_bytes()
+                .iter()
+                .fold(hash, |hash, byte| {
+                    (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+                })
+        })
+    }
+
+    /// Remove every combination containing one of `cards`
+    pub fn without(mut self, cards: impl IntoIterator<Item = Card>) -> Self {
+        for card in cards {
+            for other in deck() {
+                if other != card {
+                    self.set_weight([card, other], 0.0);
+                }
+            }
+        }
+        self
+    }
+
+    /// Average weight of the combinations in each cell of a `HandChart`
+    pub fn class_weights(&self) -> [[f64; 13]; 13] {
+        let mut sums = [[0.0; 13]; 13];
+        let mut counts = [[0.0; 13]; 13];
+        for (hand, weight) in all_combos().zip(self.0.iter()) {
+            let (x, y) = index(OpeningHand::from(hand));
+            sums[x][y] += weight;
+            counts[x][y] += 1.0;
+        }
+        for x in 0..13 {
+            for y in 0..13 {
+                sums[x][y] /= counts[x][y];
+            }
+        }
+        sums
+    }
+
+    /// Fraction of live combinations each combination beats on `board` in `variant`. Tied
+    /// combinations beat the same fraction.
+    fn percentiles(board: Board, variant: Variant) -> Vec<(usize, f64)> {
+        let board_cards = board.into_iter().collect::<Vec<_>>();
+        let live = all_combos()
+            .enumerate()
+            .filter(|(_, hand)| !hand.iter().any(|c| board_cards.contains(c)));
+        match board {
+            Board::PreFlop => {
+                fractions_beaten(live.map(|(i, hand)| (i, OpeningHand::from(hand).chen_score())))
+            }
+            _ => fractions_beaten(
+                live.map(|(i, hand)| (i, variant.best_rank_in(hand.into_iter().chain(board)))),
+            ),
+        }
+    }
+
+    /// Combinations of each hand class removed by the hero holding `hand`, and the resulting
+    /// change in the share of value hands to bluffs
+    pub fn blockers(&self, hand: &[Card], board: Board, variant: Variant) -> BlockerReport {
+        let board_cards = board.into_iter().collect::<Vec<_>>();
+        let combos = all_combos().collect::<Vec<_>>();
+        // The best card of a three-flush suit not already on the board makes the nut flush
+        let nut_card = Suite::ALL
+            .into_iter()
+            .find(|s| board_cards.iter().filter(|c| c.1 == *s).count() >= 3)
+            .and_then(|s| {
+                Face::ALL
+                    .into_iter()
+                    .map(|face| Card(face, s))
+                    .filter(|card| !board_cards.contains(card))
+                    .max_by_key(|card| card.0.high())
+            });
+        let mut classes: Vec<Blocked> = vec![];
+        let mut value = (0.0, 0.0);
+        let mut bluffs = (0.0, 0.0);
+        for (i, percentile) in Self::percentiles(board, variant) {
+            let weight = self.0[i];
+            if weight == 0.0 {
+                continue;
+            }
+            let combo = combos[i];
+            let after = if combo.iter().any(|c| hand.contains(c)) {
+                0.0
+            } else {
+                weight
+            };
+            let class = match board {
+                Board::PreFlop => OpeningHand::from(combo).to_string(),
+                _ => match variant.best_rank_in(combo.into_iter().chain(board)).kind() {
+                    RankKind::Flush if nut_card.is_some_and(|card| combo.contains(&card)) => {
+                        "Nut flush".to_string()
+                    }
+                    kind => kind.to_string(),
+                },
+            };
+            match classes.iter_mut().find(|b| b.class == class) {
+                Some(blocked) => {
+                    blocked.before += weight;
+                    blocked.after += after;
+                }
+                None => classes.push(Blocked {
+                    class,
+                    before: weight,
+                    after,
+                }),
+            }
+            if percentile >= VALUE_PERCENTILE {
+                value.0 += weight;
+                value.1 += after;
+            } else if percentile < BLUFF_PERCENTILE {
+                bluffs.0 += weight;
+                bluffs.1 += after;
+            }
+        }
+        classes.retain(|b| b.after < b.before);
+        classes.sort_by(|a, b| (b.before - b.after).total_cmp(&(a.before - a.after)));
+        let ratio = |(value, bluffs): (f64, f64)| (bluffs > 0.0).then(|| value / bluffs);
+        BlockerReport {
+            classes,
+            value_to_bluff: (ratio((value.0, bluffs.0)), ratio((value.1, bluffs.1))),
+        }
+    }
+
+    /// Model of how this range responds to bets on `board`
+    pub fn fold_model(&self, profile: &Profile, board: Board, variant: Variant) -> FoldModel {
+        let board_cards = board.into_iter().collect::<Vec<_>>();
+        let combos = all_combos().collect::<Vec<_>>();
+        let live = Self::percentiles(board, variant)
+            .into_iter()
+            .filter(|(i, _)| self.0[*i] > 0.0)
+            .collect::<Vec<_>>();
+        // Percentiles within the range rather than among every combination
+        let total = live.iter().map(|(i, _)| self.0[*i]).sum::<f64>();
+        let mut below = 0.0;
+        let hands = live
+            .into_iter()
+            .map(|(i, _)| {
+                let percentile = below / total;
+                below += self.0[i];
+                (
+                    combos[i],
+                    self.0[i],
+                    percentile,
+                    Draws::find(variant, combos[i], &board_cards),
+                )
+            })
+            .collect();
+        FoldModel {
+            profile: *profile,
+            hands,
+        }
+    }
+
+    /// Reweight the range according to the actions an opponent took on each street.
+    /// Strong hands and draws are kept when an opponent bets or raises, weak hands when they
+    /// check, and hands that would continue are removed when they fold.
+    pub fn narrow(
+        &mut self,
+        profile: &Profile,
+        board: Board,
+        variant: Variant,
+        actions: &[(Street, Action)],
+    ) {
+        *self = self.clone().without(board);
+        for street in Street::ALL {
+            let Some(street_board) = board.at(street) else {
+                break;
+            };
+            let mut street_actions = actions.iter().filter(|(s, _)| *s == street).peekable();
+            if street_actions.peek().is_none() {
+                continue;
+            }
+            let percentiles = Self::percentiles(street_board, variant);
+            let board_cards = street_board.into_iter().collect::<Vec<_>>();
+            let combos = all_combos().collect::<Vec<_>>();
+            for (_, action) in street_actions {
+                for (i, percentile) in percentiles.iter().copied() {
+                    if self.0[i] == 0.0 {
+                        continue;
+                    }
+                    let draws = Draws::find(variant, combos[i], &board_cards);
+                    self.0[i] *= profile.likelihood(*action, percentile, draws);
+                }
+            }
+        }
+    }
+}
+
+/// Fraction of the scored entries each entry scores above, the same for every entry of a tie
+fn fractions_beaten<T: PartialOrd>(scored: impl Iterator<Item = (usize, T)>) -> Vec<(usize, f64)> {
+    let mut scored = scored.collect::<Vec<_>>();
+    scored.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    let len = scored.len() as f64;
+    let mut below = 0;
+    (0..scored.len())
+        .map(|n| {
+            if n > 0 && scored[n].1 > scored[n - 1].1 {
+                below = n;
+            }
+            (scored[n].0, below as f64 / len)
+        })
+        .collect()
+}
+
+/// Hands at or above this percentile are bet for value
+const VALUE_PERCENTILE: f64 = 0.8;
+/// Hands below this percentile can only win by bluffing
+const BLUFF_PERCENTILE: f64 = 0.5;
+
+/// Weighted combinations of one hand class before and after card removal
+#[derive(Debug, Clone, PartialEq)]
+pub struct Blocked {
+    pub class: String,
+    pub before: f64,
+    pub after: f64,
+}
+
+/// How the hero's hole cards change an opponent's range
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockerReport {
+    /// Hand classes losing combinations, most removed first
+    pub classes: Vec<Blocked>,
+    /// Value hands per bluff before and after removing the hero's cards, or `None` when there
+    /// are no bluffs left
+    pub value_to_bluff: (Option<f64>, Option<f64>),
+}
+
+impl std::fmt::Display for BlockerReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for blocked in &self.classes {
+            writeln!(
+                f,
+                "{}: {:.1} of {:.1} combos blocked",
+                blocked.class,
+                blocked.before - blocked.after,
+                blocked.before
+            )?;
+        }
+        let ratio = |ratio: Option<f64>| match ratio {
+            Some(ratio) => format!("{ratio:.2}"),
+            None => "no bluffs".to_string(),
+        };
+        write!(
+            f,
+            "Value to bluff ratio: {} to {}",
+            ratio(self.value_to_bluff.0),
+            ratio(self.value_to_bluff.1)
+        )
+    }
+}
+
+/// How likely an opponent holding a range is to fold to bets of different sizes
+#[derive(Debug, Clone, PartialEq)]
+pub struct FoldModel {
+    profile: Profile,
+    /// Cards, weight, percentile and draws of every live combination
+    hands: Vec<([Card; 2], f64, f64, Draws)>,
+}
+
+impl FoldModel {
+    /// Each combination with its weight and chance of folding to `bet` more chips into `pot`
+    fn responses(&self, bet: usize, pot: usize) -> impl Iterator<Item = ([Card; 2], f64, f64)> {
+        // Profiles describe a pot sized bet, smaller bets are defended more often
+        let defence = pot as f64 / (pot + bet) as f64;
+        let profile = Profile {
+            looseness: (self.profile.looseness * 2.0 * defence).clamp(0.01, 0.99),
+            ..self.profile
+        };
+        self.hands
+            .iter()
+            .map(move |(hand, weight, percentile, draws)| {
+                let fold = profile.likelihood(Action::Fold, *percentile, *draws);
+                let call = profile.likelihood(Action::Call(bet), *percentile, *draws);
+                (*hand, *weight, fold / (fold + call))
+            })
+    }
+
+    /// Chance of folding when facing `bet` more chips into `pot`
+    pub fn fold_chance(&self, bet: usize, pot: usize) -> f64 {
+        if bet == 0 {
+            return 0.0;
+        }
+        let (mut folds, mut total) = (0.0, 0.0);
+        for (_, weight, fold) in self.responses(bet, pot) {
+            folds += weight * fold;
+            total += weight;
+        }
+        if total == 0.0 { 0.0 } else { folds / total }
+    }
+
+    /// Shares of the calling and of the folding weight that hold one of `cards`
+    pub fn blocked(&self, cards: &[Card], bet: usize, pot: usize) -> (f64, f64) {
+        let (mut calls, mut folds) = ((0.0, 0.0), (0.0, 0.0));
+        for (hand, weight, fold) in self.responses(bet, pot) {
+            let blocked = if hand.iter().any(|c| cards.contains(c)) {
+                1.0
+            } else {
+                0.0
+            };
+            calls.0 += blocked * weight * (1.0 - fold);
+            calls.1 += weight * (1.0 - fold);
+            folds.0 += blocked * weight * fold;
+            folds.1 += weight * fold;
+        }
+        let share = |(blocked, total): (f64, f64)| if total == 0.0 { 0.0 } else { blocked / total };
+        (share(calls), share(folds))
+    }
+}
+
+/// Random hands used to find the score threshold of an `OmahaRange`
+const OMAHA_THRESHOLD_SAMPLES: usize = 2000;
+
+/// Pre-flop strength of an Omaha hand, summing the Chen scores of every pair it can play
+pub fn omaha_score(hand: &[Card]) -> f64 {
+    hand.iter()
+        .tuple_combinations()
+        .map(|(a, b)| OpeningHand::from([*a, *b]).chen_score())
+        .sum()
+}
+
+/// Omaha hands an opponent plays, as the strongest fraction of all hands
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OmahaRange {
+    /// Fraction of hands played, between 0 and 1
+    pub top: f64,
+}
+
+impl Default for OmahaRange {
+    fn default() -> Self {
+        OmahaRange { top: 1.0 }
+    }
+}
+
+impl OmahaRange {
+    /// Lowest `omaha_score` of a played hand with `hole_cards` cards dealt from `deck`
+    pub fn threshold(&self, deck: &[Card], hole_cards: usize) -> f64 {
+        if self.top >= 1.0 {
+            return f64::MIN;
+        }
+        let rng = &mut rand::rng();
+        let mut scores = (0..OMAHA_THRESHOLD_SAMPLES)
+            .map(|_| {
+                omaha_score(
+                    &deck
+                        .choose_multiple(rng, hole_cards)
+                        .copied()
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        scores.sort_by(|a, b| b.total_cmp(a));
+        let played = (self.top.max(0.0) * scores.len() as f64) as usize;
+        scores[played.min(scores.len() - 1)]
+    }
+}
+
+/// Hands an opponent may hold, as weighted two card combinations or, in Omaha, the strongest
+/// fraction of all hands
+#[derive(Debug, Clone, PartialEq)]
+pub enum OpponentRange {
+    Combos(Range),
+    Omaha(OmahaRange),
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// Tendencies of an opponent, used to interpret their actions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Profile {
+    /// Fraction of hands an opponent continues with when facing a bet
+    pub looseness: f64,
+    /// Tendency to bet and raise rather than check and call, between 0 and 1
+    pub aggression: f64,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::BALANCED
+    }
+}
+
+impl Profile {
+    pub const BALANCED: Profile = Profile {
+        looseness: 0.5,
+        aggression: 0.5,
+    };
+    pub const TIGHT: Profile = Profile {
+        looseness: 0.3,
+        aggression: 0.4,
+    };
+    pub const LOOSE: Profile = Profile {
+        looseness: 0.7,
+        aggression: 0.3,
+    };
+    pub const MANIAC: Profile = Profile {
+        looseness: 0.8,
+        aggression: 0.9,
+    };
+    pub const PRESETS: [(&'static str, Profile); 4] = [
+        ("tight", Self::TIGHT),
+        ("balanced", Self::BALANCED),
+        ("loose", Self::LOOSE),
+        ("maniac", Self::MANIAC),
+    ];
+
+    /// Relative likelihood that a hand at `percentile` with `draws` takes `action`
+    fn likelihood(&self, action: Action, percentile: f64, draws: Draws) -> f64 {
+        let above = |threshold: f64| sigmoid((percentile - threshold) / 0.05);
+        let draw = if draws.flush_draw || draws.open_ended {
+            1.0
+        } else if draws.gutshot {
+            0.5
+        } else {
+            0.0
+        };
+        let continuing = above(1.0 - self.looseness);
+        match action {
+            Action::Fold => 1.0 - continuing,
+            Action::Check => 1.0 - self.aggression * above(0.8),
+            Action::Call(_) => continuing.max(draw * self.looseness),
+            Action::Bet(_) => above(1.0 - 0.35 * (0.5 + self.aggression))
+                .max(draw * self.aggression)
+                .max(0.3 * self.aggression),
+            Action::Raise(_) => above(1.0 - 0.2 * (0.5 + self.aggression))
+                .max(draw * self.aggression * 0.8)
+                .max(0.15 * self.aggression),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Raising keeps the strongest hands and folding removes them
+    #[test]
+    fn narrowing() {
+        let weight = |range: &Range, hand: [Card; 2]| range.0[combo_index(hand)];
+        let (aces, trash) = (cards!("As", "Ad"), cards!("7c", "2d"));
+        let mut raised = Range::full();
+        raised.narrow(
+            &Profile::default(),
+            Board::PreFlop,
+            Variant::Holdem,
+            &[(Street::PreFlop, Action::Raise(6))],
+        );
+        assert!(weight(&raised, aces) > 10.0 * weight(&raised, trash));
+        let mut folded = Range::full();
+        folded.narrow(
+            &Profile::default(),
+            Board::PreFlop,
+            Variant::Holdem,
+            &[(Street::PreFlop, Action::Fold)],
+        );
+        assert!(weight(&folded, trash) > 10.0 * weight(&folded, aces));
+        // Actions on later streets don't count before their cards are dealt
+        let mut unchanged = Range::full();
+        unchanged.narrow(
+            &Profile::default(),
+            Board::PreFlop,
+            Variant::Holdem,
+            &[(Street::Flop, Action::Bet(10))],
+        );
+        assert_eq!(unchanged, Range::full());
+    }
+
+    /// The king of a suit makes the nut flush when its ace is on the board, and a range
+    /// without bluffs has no ratio
+    #[test]
+    fn nut_flush_blockers() {
+        let board = Board::from_cards(&cards!("Ah", "7h", "2h")).unwrap();
+        let report = Range::full().blockers(&cards!("Kh", "Qc"), board, Variant::Holdem);
+        let nut = report
+            .classes
+            .iter()
+            .find(|b| b.class == "Nut flush")
+            .unwrap();
+        assert!(nut.after == 0.0 && nut.before > 0.0);
+
+        let mut value_only = Range::full();
+        for (i, weight) in value_only.0.iter_mut().enumerate() {
+            if i != combo_index(cards!("Kh", "Qh")) {
+                *weight = 0.0;
+            }
+        }
+        let report = value_only.blockers(&cards!("Kh", "Qc"), board, Variant::Holdem);
+        assert_eq!(report.value_to_bluff, (None, None));
+        assert!(report.to_string().ends_with("no bluffs to no bluffs"));
+    }
+
+    /// Combinations that tie beat the same fraction, and short deck flushes beat full houses
+    #[test]
+    fn tied_percentiles() {
+        let royal = Board::from_cards(&cards!("Ah", "Kh", "Qh", "Jh", "Th")).unwrap();
+        assert!(
+            Range::percentiles(royal, Variant::Holdem)
+                .iter()
+                .all(|(_, percentile)| *percentile == 0.0)
+        );
+
+        let board = Board::from_cards(&cards!("9h", "9s", "6h", "7h", "Tc")).unwrap();
+        let percentile = |variant, hand| {
+            Range::percentiles(board, variant)
+                .into_iter()
+                .find(|(i, _)| *i == combo_index(hand))
+                .unwrap()
+                .1
+        };
+        let (flush, full_house) = (cards!("Ah", "Kh"), cards!("6s", "6c"));
+        assert!(percentile(Variant::Holdem, full_house) > percentile(Variant::Holdem, flush));
+        assert!(percentile(Variant::ShortDeck, flush) > percentile(Variant::ShortDeck, full_house));
+        assert_eq!(
+            percentile(Variant::Holdem, cards!("Ad", "Kd")),
+            percentile(Variant::Holdem, cards!("As", "Kc"))
+        );
+    }
+}
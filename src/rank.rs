@@ -16,22 +16,53 @@ pub enum RankKind {
 pub struct Ranking {
     kind: RankKind,
     hand_descending: [usize; 5],
+    variant: Variant,
+}
+
+impl Ranking {
+    pub fn kind(&self) -> RankKind {
+        self.kind
+    }
+
+    /// Stable identifier of the hand's equivalence class, from 1 for a royal flush to 7,462
+    /// for seven-five high in hold'em. Short deck numbers its fewer classes the same way.
+    /// Hands with cards outside the variant's deck have no class.
+    pub fn class(&self) -> Option<usize> {
+        CLASSES
+            .get(&(self.variant, self.kind, self.hand_descending))
+            .copied()
+    }
+
+    /// Order of the hand kind in this ranking's variant
+    fn kind_order(&self) -> usize {
+        match (self.variant, self.kind) {
+            (Variant::ShortDeck, RankKind::Flush) => RankKind::FullHouse as usize,
+            (Variant::ShortDeck, RankKind::FullHouse) => RankKind::Flush as usize,
+            (_, kind) => kind as usize,
+        }
+    }
 }
 
 impl PartialOrd for Ranking {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        use std::cmp::Ordering::*;
-        match self.kind.cmp(&other.kind) {
-            Greater => Some(Greater),
-            Less => Some(Less),
-            Equal => Some(self.hand_descending.cmp(&other.hand_descending)),
-        }
+        Some(self.cmp(other))
     }
 }
 
+/// Rankings order by the kind of hand in their variant and then by their faces. The kind and
+/// the variant themselves only separate rankings of different variants, so that only equal
+/// rankings compare equal.
 impl Ord for Ranking {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+        let key = |r: &Self| {
+            (
+                r.kind_order(),
+                r.hand_descending,
+                r.kind,
+                r.variant as usize,
+            )
+        };
+        key(self).cmp(&key(other))
     }
 }
 
@@ -56,12 +87,91 @@ impl std::fmt::Display for RankKind {
     }
 }
 
+/// Describes the hand, such as "Pair of Kings, Ace-Queen-Nine kickers"
 impl std::fmt::Display for Ranking {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.kind)
+        let faces = self
+            .hand_descending
+            .map(|v| Face::from_value(v).unwrap_or(Face::Ace));
+        let names = |faces: &[Face]| faces.iter().map(|f| f.name()).join("-");
+        let kickers = |faces: &[Face]| match faces.len() {
+            1 => format!(", {} kicker", faces[0].name()),
+            _ => format!(", {} kickers", names(faces)),
+        };
+        match self.kind {
+            RankKind::HighCard => write!(f, "{}-high{}", faces[0].name(), kickers(&faces[1..])),
+            RankKind::OnePair => {
+                write!(f, "Pair of {}{}", faces[0].plural(), kickers(&faces[2..]))
+            }
+            RankKind::TwoPairs => write!(
+                f,
+                "{} and {}{}",
+                faces[0].plural(),
+                faces[2].plural(),
+                kickers(&faces[4..])
+            ),
+            RankKind::ThreeKind => {
+                write!(f, "Three {}{}", faces[0].plural(), kickers(&faces[3..]))
+            }
+            RankKind::Straight => write!(f, "{}-high straight", faces[0].name()),
+            RankKind::Flush => write!(f, "{} flush", names(&faces)),
+            RankKind::FullHouse => {
+                write!(f, "{} full of {}", faces[0].plural(), faces[3].plural())
+            }
+            RankKind::FourKind => write!(f, "Four {}{}", faces[0].plural(), kickers(&faces[4..])),
+            RankKind::StraightFlush => write!(f, "{}-high straight flush", faces[0].name()),
+            RankKind::RoyalFlush => write!(f, "Royal flush"),
+        }
     }
 }
 
+/// What tells distinct hands of each variant apart
+type ClassKey = (Variant, RankKind, [usize; 5]);
+
+/// Identifier of every distinct hand of each variant, from 1 for the best hand up
+static CLASSES: LazyLock<HashMap<ClassKey, usize>> = LazyLock::new(|| {
+    let mut classes = HashMap::new();
+    for variant in Variant::ALL {
+        let mut rankings = vec![];
+        for faces in variant.faces().iter().combinations_with_replacement(5) {
+            // Repeated faces get different suits, and unpaired hands come in a flush and not
+            let mut seen: Vec<Face> = vec![];
+            let cards = faces
+                .iter()
+                .map(|face| {
+                    let copies = seen.iter().filter(|f| *f == *face).count();
+                    seen.push(**face);
+                    (**face, copies)
+                })
+                .collect::<Vec<_>>();
+            if cards.iter().any(|(_, copies)| *copies >= 4) {
+                continue;
+            }
+            let hand = |suit: &dyn Fn(usize, usize) -> usize| {
+                Hand::from(
+                    cards
+                        .iter()
+                        .enumerate()
+                        .map(|(i, (face, copies))| Card(*face, Suite::ALL[suit(i, *copies)])),
+                )
+            };
+            if cards.iter().all(|(_, copies)| *copies == 0) {
+                rankings.push(variant.rank(&hand(&|_, _| 0)));
+                rankings.push(variant.rank(&hand(&|i, _| (i == 1) as usize)));
+            } else {
+                rankings.push(variant.rank(&hand(&|_, copies| copies)));
+            }
+        }
+        rankings.sort_by(|a, b| b.cmp(a));
+        for (i, ranking) in rankings.into_iter().enumerate() {
+            classes.insert((variant, ranking.kind, ranking.hand_descending), i + 1);
+        }
+    }
+    classes
+});
+
+use std::{collections::HashMap, sync::LazyLock};
+
 use itertools::Itertools;
 
 use crate::cards::*;
@@ -74,29 +184,51 @@ impl From<Hand> for Ranking {
 
 impl From<&Hand> for Ranking {
     fn from(hand: &Hand) -> Self {
+        Self::new(hand, Variant::Holdem)
+    }
+}
+
+impl Ranking {
+    pub fn new(hand: &Hand, variant: Variant) -> Self {
         use Face::*;
-        let mut face_values = hand.face_values();
-        face_values.sort();
-        let mut hand_descending = face_values.clone();
-        hand_descending.reverse();
+        // Bigger groups first, then higher faces, so pairs compare before their kickers
+        let groups = hand
+            .face_values()
+            .into_iter()
+            .counts()
+            .into_iter()
+            .sorted_by(|a, b| (b.1, b.0).cmp(&(a.1, a.0)))
+            .collect::<Vec<_>>();
+        let mut hand_descending = [0; 5];
+        for (slot, face) in hand_descending.iter_mut().zip(
+            groups
+                .iter()
+                .flat_map(|(face, count)| std::iter::repeat_n(*face, *count)),
+        ) {
+            *slot = face;
+        }
+        // The ace plays low below the lowest face of the deck in a wheel
+        let lowest = variant.faces()[0];
+        let wheel = [
+            Ace.high(),
+            lowest.high() + 3,
+            lowest.high() + 2,
+            lowest.high() + 1,
+            lowest.high(),
+        ];
+        let is_straight = groups.len() == 5
+            && (hand_descending[0] - hand_descending[4] == 4 || hand_descending == wheel);
+        if hand_descending == wheel {
+            hand_descending = [wheel[1], wheel[2], wheel[3], wheel[4], Ace.low()];
+        }
         let r = |kind| Ranking {
             kind,
             hand_descending,
+            variant,
         };
-        // Check for flush and straight hands
         let is_flush = hand.suites_iter().all(|c| c == hand.0[0].1);
-        let is_high_straight = face_values == [Ace, Ten, Jack, Queen, King].map(|f| f as usize);
-        let is_straight = face_values
-            == [
-                face_values[0],
-                face_values[0] + 1,
-                face_values[0] + 2,
-                face_values[0] + 3,
-                face_values[0] + 4,
-            ]
-            || is_high_straight;
         match (is_straight, is_flush) {
-            (true, true) if is_high_straight => {
+            (true, true) if hand_descending[0] == Ace.high() => {
                 return r(RankKind::RoyalFlush);
             }
             (true, true) => return r(RankKind::StraightFlush),
@@ -104,35 +236,393 @@ impl From<&Hand> for Ranking {
             (false, true) => return r(RankKind::Flush),
             _ => (),
         };
-        // Check for full house and N pairs
-        let mut face_value_counts = [0; 14];
-        face_values.iter().for_each(|f| face_value_counts[*f] += 1);
-        let mut max = 0;
-        let mut two_kind_count = 0; // Used to detect full house and two pair
-        for count in face_value_counts {
-            if max < count {
-                max = count;
-            }
-            if count == 2 {
-                two_kind_count += 1;
-            }
-        }
-        return r(match (max, two_kind_count) {
+        r(match (groups[0].1, groups[1].1) {
             (4, _) => RankKind::FourKind,
-            (3, 1) => RankKind::FullHouse,
+            (3, 2) => RankKind::FullHouse,
             (3, _) => RankKind::ThreeKind,
-            (_, 2) => RankKind::TwoPairs,
+            (2, 2) => RankKind::TwoPairs,
             (2, _) => RankKind::OnePair,
             (_, _) => RankKind::HighCard,
-        });
+        })
+    }
+}
+
+impl Variant {
+    pub fn rank(self, hand: &Hand) -> Ranking {
+        Ranking::new(hand, self)
     }
+
+    /// Best five card hand among `cards`
+    pub fn best_hand_in(self, cards: impl IntoIterator<Item = Card>) -> Hand {
+        cards
+            .into_iter()
+            .combinations(5)
+            .map(Hand::from)
+            .max_by_key(|hand| self.rank(hand))
+            .unwrap()
+    }
+
+    /// Ranking of the best five card hand among `cards`
+    pub fn best_rank_in(self, cards: impl IntoIterator<Item = Card>) -> Ranking {
+        self.rank(&self.best_hand_in(cards))
+    }
+
+    /// Ranking of the best hand a player holding `hole` can make with `board`.
+    /// Omaha hands use exactly two hole cards and three from the board.
+    pub fn best_rank_for(self, hole: &[Card], board: &[Card]) -> Ranking {
+        if self.omaha() {
+            omaha_hands(hole, board)
+                .map(|hand| self.rank(&Hand::from(hand)))
+                .max()
+                .unwrap()
+        } else {
+            self.best_rank_in(hole.iter().chain(board).copied())
+        }
+    }
+
+    /// Best eight-or-better low a player holding `hole` can make with `board`, following the
+    /// same hole card rules as `best_rank_for`
+    pub fn best_low_for(self, hole: &[Card], board: &[Card]) -> Option<Low> {
+        if self.omaha() {
+            omaha_hands(hole, board).filter_map(Low::find).max()
+        } else {
+            Low::find(hole.iter().chain(board).copied())
+        }
+    }
+}
+
+/// Every five card hand made of two hole cards and three board cards
+fn omaha_hands(hole: &[Card], board: &[Card]) -> impl Iterator<Item = Vec<Card>> {
+    hole.iter()
+        .copied()
+        .combinations(2)
+        .cartesian_product(board.iter().copied().combinations(3))
+        .map(|(hole, board)| hole.into_iter().chain(board).collect())
 }
 
-pub fn best_hand_in(cards: impl IntoIterator<Item = Card>) -> Hand {
-    cards
+/// Highest card allowed in a qualifying low
+const LOW_QUALIFIER: usize = 8;
+
+/// A low hand, where aces are low and straights and flushes don't count. Better lows compare
+/// greater.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Low {
+    /// Pairs and worse, which only matter when no unpaired low can be made
+    kind: RankKind,
+    /// Face values of the biggest groups first, then from the highest down
+    faces_descending: [usize; 5],
+}
+
+impl Low {
+    /// Best eight-or-better low among `cards`, which needs five different faces of eight or
+    /// below
+    pub fn find(cards: impl IntoIterator<Item = Card>) -> Option<Self> {
+        let faces = cards
+            .into_iter()
+            .map(|c| c.0.low())
+            .filter(|f| *f <= LOW_QUALIFIER)
+            .sorted()
+            .dedup()
+            .take(5)
+            .collect::<Vec<_>>();
+        let mut faces_descending: [usize; 5] = faces.try_into().ok()?;
+        faces_descending.reverse();
+        Some(Low {
+            kind: RankKind::HighCard,
+            faces_descending,
+        })
+    }
+
+    /// Best ace-to-five low among `cards`, as played in Razz, with no qualifier
+    pub fn ace_to_five(cards: impl IntoIterator<Item = Card>) -> Option<Self> {
+        cards
+            .into_iter()
+            .combinations(5)
+            .map(|hand| {
+                // Bigger groups first, so pairs compare by the paired face
+                let groups = hand
+                    .iter()
+                    .map(|c| c.0.low())
+                    .counts()
+                    .into_iter()
+                    .sorted_by(|a, b| (b.1, b.0).cmp(&(a.1, a.0)))
+                    .collect::<Vec<_>>();
+                let kind = match (groups[0].1, groups.get(1).map_or(0, |g| g.1)) {
+                    (4, _) => RankKind::FourKind,
+                    (3, 2) => RankKind::FullHouse,
+                    (3, _) => RankKind::ThreeKind,
+                    (2, 2) => RankKind::TwoPairs,
+                    (2, _) => RankKind::OnePair,
+                    _ => RankKind::HighCard,
+                };
+                let faces = groups
+                    .iter()
+                    .flat_map(|(face, count)| std::iter::repeat_n(*face, *count))
+                    .collect::<Vec<_>>();
+                Low {
+                    kind,
+                    faces_descending: faces.try_into().unwrap(),
+                }
+            })
+            .max()
+    }
+}
+
+impl PartialOrd for Low {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Low {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (other.kind, other.faces_descending).cmp(&(self.kind, self.faces_descending))
+    }
+}
+
+impl std::fmt::Display for Low {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let faces = self
+            .faces_descending
+            .map(|v| Face::from_value(v).unwrap().to_string());
+        if self.kind != RankKind::HighCard {
+            write!(f, "{}, ", self.kind)?;
+        }
+        write!(f, "{} low", faces.join("-"))
+    }
+}
+
+/// Split `amount` evenly between the best of `hands`, ignoring missing ones
+fn split<T: Ord + Copy>(hands: &[Option<T>], amount: f64) -> Vec<f64> {
+    let best = hands.iter().max().copied().flatten();
+    let winners = hands.iter().filter(|h| h.is_some() && **h == best).count();
+    hands
+        .iter()
+        .map(|h| {
+            if h.is_some() && *h == best {
+                amount / winners as f64
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// Shares of the pot each player wins at a split-pot showdown with the high and the low
+/// hand. Half goes to the best high hands and half to the best qualifying lows, with one
+/// side scooping when nobody has a hand for the other. Tied players split their half.
+pub fn pot_shares(highs: &[Option<Ranking>], lows: &[Option<Low>]) -> Vec<(f64, f64)> {
+    let half = match (
+        highs.iter().any(Option::is_some),
+        lows.iter().any(Option::is_some),
+    ) {
+        (true, true) => 0.5,
+        (true, false) => 1.0,
+        (false, _) => 0.0,
+    };
+    split(highs, half)
         .into_iter()
-        .combinations(5)
-        .map(Hand::from)
-        .max_by(|a, b| Ranking::from(a).cmp(&Ranking::from(b)))
-        .unwrap()
+        .zip(split(lows, 1.0 - half))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// Every five card hand falls into one of 7,462 classes, with a known number of classes
+    /// and hands of each kind
+    #[test]
+    fn hand_classes() {
+        let mut classes: HashMap<([usize; 5], RankKind), (Ranking, usize)> = HashMap::new();
+        for hand in deck().into_iter().combinations(5) {
+            let ranking = Ranking::from(Hand::from(hand));
+            classes
+                .entry((ranking.hand_descending, ranking.kind))
+                .or_insert((ranking, 0))
+                .1 += 1;
+        }
+        assert_eq!(classes.len(), 7462);
+        let mut by_kind: HashMap<RankKind, (usize, usize)> = HashMap::new();
+        for (ranking, hands) in classes.values() {
+            let kind = by_kind.entry(ranking.kind).or_default();
+            kind.0 += 1;
+            kind.1 += hands;
+        }
+        let expected = [
+            (RankKind::HighCard, 1277, 1302540),
+            (RankKind::OnePair, 2860, 1098240),
+            (RankKind::TwoPairs, 858, 123552),
+            (RankKind::ThreeKind, 858, 54912),
+            (RankKind::Straight, 10, 10200),
+            (RankKind::Flush, 1277, 5108),
+            (RankKind::FullHouse, 156, 3744),
+            (RankKind::FourKind, 156, 624),
+            (RankKind::StraightFlush, 9, 36),
+            (RankKind::RoyalFlush, 1, 4),
+        ];
+        for (kind, class_count, hand_count) in expected {
+            assert_eq!(by_kind[&kind], (class_count, hand_count), "{kind}");
+        }
+        // Classes are strictly ordered, and every class of a kind beats every class below it
+        let mut sorted = classes.values().map(|(r, _)| *r).collect::<Vec<_>>();
+        sorted.sort();
+        for pair in sorted.windows(2) {
+            assert!(pair[0] < pair[1]);
+            assert!(pair[0].kind <= pair[1].kind);
+        }
+        let wheel = Ranking::from(Hand(cards!("As", "2d", "3c", "4h", "5s")));
+        let six_high = Ranking::from(Hand(cards!("2s", "3d", "4c", "5h", "6s")));
+        let aces = Ranking::from(Hand(cards!("As", "Ad", "2c", "3h", "4s")));
+        let kings = Ranking::from(Hand(cards!("Ks", "Kd", "Qc", "Jh", "9s")));
+        assert_eq!(wheel.kind, RankKind::Straight);
+        assert!(wheel < six_high);
+        assert!(aces > kings);
+        assert_eq!(
+            sorted[0],
+            Ranking::from(Hand(cards!("2s", "3d", "4c", "5h", "7s")))
+        );
+        // Identifiers count down from the best class
+        for (i, ranking) in sorted.iter().enumerate() {
+            assert_eq!(ranking.class(), Some(7462 - i));
+        }
+        assert_eq!(
+            Ranking::from(Hand(cards!("Ks", "Kd", "Ac", "Qh", "9s"))).to_string(),
+            "Pair of Kings, Ace-Queen-Nine kickers"
+        );
+        assert_eq!(wheel.to_string(), "Five-high straight");
+    }
+
+    /// Omaha hands use exactly two hole cards and three board cards
+    #[test]
+    fn omaha_two_from_hand() {
+        let hole = cards!("Ah", "Kd", "7c", "2s");
+        let board = cards!("Qh", "Jh", "Th", "9h", "3c");
+        assert_eq!(
+            Variant::Holdem.best_rank_for(&hole, &board).kind(),
+            RankKind::Flush
+        );
+        let omaha = Variant::Omaha.best_rank_for(&hole, &board);
+        assert_eq!(omaha.kind(), RankKind::Straight);
+        assert_eq!(omaha.to_string(), "Ace-high straight");
+        // Four of a kind on the board plays only one of its cards
+        let quads = Variant::Omaha.best_rank_for(
+            &cards!("Kh", "Ks", "2d", "3d", "4c"),
+            &cards!("As", "Ad", "Ac", "Ah", "5c"),
+        );
+        assert_eq!(quads.kind(), RankKind::FullHouse);
+        assert!(HoleCards::new(&cards!("Ah", "Kd", "7c", "2s", "3s", "4s")).is_none());
+        assert!(HoleCards::new(&cards!("Ah")).is_none());
+    }
+
+    /// Lows need five faces of eight or below, and split pots scoop or quarter
+    #[test]
+    fn hi_lo_showdowns() {
+        let board = cards!("2c", "5d", "8h", "Ks", "Qs");
+        let ace_three = Variant::OmahaHiLo.best_low_for(&cards!("Ah", "3d", "Kd", "Kc"), &board);
+        let eight = Variant::OmahaHiLo.best_low_for(&cards!("4h", "7d", "Jd", "Jc"), &board);
+        assert_eq!(ace_three.unwrap().to_string(), "8-5-3-2-A low");
+        assert!(ace_three > eight);
+        assert_eq!(
+            Variant::OmahaHiLo.best_low_for(&cards!("9h", "Td", "Jd", "Jc"), &board),
+            None
+        );
+        assert_eq!(Low::find(cards!("Ah", "2d", "3c", "4s", "9h")), None);
+
+        let high = |cards: [Card; 5]| Some(Ranking::from(Hand(cards)));
+        let (set, pair) = (
+            high(cards!("Ks", "Kd", "Kc", "8h", "5d")),
+            high(cards!("Qs", "Qd", "Ks", "8h", "5d")),
+        );
+        // Nobody qualifies for low, so the best high scoops
+        assert_eq!(
+            pot_shares(&[set, pair], &[None, None]),
+            [(1.0, 0.0), (0.0, 0.0)]
+        );
+        assert_eq!(
+            pot_shares(&[set, pair], &[None, ace_three]),
+            [(0.5, 0.0), (0.0, 0.5)]
+        );
+        // Tied lows quarter the pot
+        assert_eq!(
+            pot_shares(&[set, pair], &[ace_three, ace_three]),
+            [(0.5, 0.25), (0.0, 0.25)]
+        );
+    }
+
+    /// Aces play high in kickers and displays, and low only in a wheel
+    #[test]
+    fn ace_high_and_low() {
+        assert_eq!((Face::Ace.high(), Face::Ace.low()), (14, 1));
+        assert_eq!(Face::Ace.cmp_high(Face::King), std::cmp::Ordering::Greater);
+        let ace_kicker = Ranking::from(Hand(cards!("9s", "9d", "Ac", "3h", "2s")));
+        let king_kicker = Ranking::from(Hand(cards!("9h", "9c", "Kc", "Qh", "Js")));
+        assert!(ace_kicker > king_kicker);
+        assert_eq!(
+            Hand(cards!("2s", "Ad", "Kc", "3h", "9s")).to_string(),
+            "AK932 [Ace-high, King-Nine-Three-Two kickers]"
+        );
+        // The ace goes below the six in a short deck wheel
+        let wheel = Variant::ShortDeck.rank(&Hand(cards!("As", "6d", "7c", "8h", "9s")));
+        let ten_high = Variant::ShortDeck.rank(&Hand(cards!("6s", "7d", "8c", "9h", "Ts")));
+        assert_eq!(wheel.kind(), RankKind::Straight);
+        assert!(wheel < ten_high);
+        assert_eq!(wheel.to_string(), "Nine-high straight");
+        assert_eq!(
+            Variant::Holdem
+                .rank(&Hand(cards!("As", "6d", "7c", "8h", "9s")))
+                .kind(),
+            RankKind::HighCard
+        );
+    }
+
+    /// Identifiers and names of the best and worst hands of each kind
+    #[test]
+    fn class_names() {
+        let named = |cards: [Card; 5]| {
+            let ranking = Ranking::from(Hand(cards));
+            (ranking.class().unwrap(), ranking.to_string())
+        };
+        assert_eq!(
+            named(cards!("As", "Ks", "Qs", "Js", "Ts")),
+            (1, "Royal flush".to_string())
+        );
+        assert_eq!(
+            named(cards!("Ks", "Qs", "Js", "Ts", "9s")),
+            (2, "King-high straight flush".to_string())
+        );
+        assert_eq!(
+            named(cards!("As", "Ad", "Ac", "Ah", "Ks")),
+            (11, "Four Aces, King kicker".to_string())
+        );
+        assert_eq!(
+            named(cards!("As", "Ad", "Ac", "Kh", "Ks")),
+            (167, "Aces full of Kings".to_string())
+        );
+        assert_eq!(
+            named(cards!("6s", "6d", "2c", "2h", "3s")),
+            (
+                named(cards!("6h", "6c", "2s", "2d", "3d")).0,
+                "Sixes and Twos, Three kicker".to_string()
+            )
+        );
+        assert_eq!(
+            named(cards!("7s", "5d", "4c", "3h", "2s")).1,
+            "Seven-high, Five-Four-Three-Two kickers"
+        );
+        // A short deck flush beats a full house, so it takes the lower identifier
+        let flush = Variant::ShortDeck.rank(&Hand(cards!("As", "Ks", "Qs", "Js", "9s")));
+        let full = Variant::ShortDeck.rank(&Hand(cards!("As", "Ad", "Ac", "Kh", "Ks")));
+        assert!(flush.class() < full.class());
+        assert_eq!(flush.to_string(), "Ace-King-Queen-Jack-Nine flush");
+        // Short deck has no twos, and only rankings in the same variant compare equal
+        let two = Variant::ShortDeck.rank(&Hand(cards!("As", "Ks", "Qs", "Js", "2s")));
+        assert_eq!(two.class(), None);
+        let holdem_full = Variant::Holdem.rank(&Hand(cards!("As", "Ad", "Ac", "Kh", "Ks")));
+        let omaha_full = Variant::Omaha.rank(&Hand(cards!("As", "Ad", "Ac", "Kh", "Ks")));
+        assert_ne!(holdem_full, omaha_full);
+        assert_ne!(holdem_full.cmp(&omaha_full), std::cmp::Ordering::Equal);
+    }
 }
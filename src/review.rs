@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+
+use crate::{
+    ai,
+    cards::{CardSet, Variant},
+    eval::Recommendation,
+    history::HandHistory,
+    preflop::HandChart,
+    range::{Profile, Range},
+    state::*,
+    stats::Tracker,
+};
+
+/// Random opponent hands and boards used to estimate equity at each decision
+const SAMPLES: usize = 1000;
+
+/// A decision made by the hero in an imported hand, compared with the engine's recommendation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    pub street: Street,
+    pub board: Board,
+    pub pot: usize,
+    pub call_price: usize,
+    pub stack: usize,
+    /// Equity against every remaining opponent
+    pub equity: f64,
+    pub taken: Action,
+    pub recommended: (Recommendation, usize),
+    /// Opening chart recommendation, when the hero was first to enter the pot
+    pub chart: Option<Recommendation>,
+    /// Estimated chips lost by taking this action instead of the recommended one
+    pub ev_loss: f64,
+}
+
+impl Decision {
+    /// The recommendation matching the action actually taken
+    pub fn taken_recommendation(&self) -> Recommendation {
+        match self.taken {
+            Action::Fold if self.call_price == 0 => Recommendation::Call,
+            Action::Fold => Recommendation::Fold,
+            Action::Check | Action::Call(_) => Recommendation::Call,
+            Action::Bet(_) | Action::Raise(_) => Recommendation::Raise,
+        }
+    }
+
+    /// Whether the action taken differs from the engine or the opening chart
+    pub fn deviates(&self) -> bool {
+        let taken = self.taken_recommendation();
+        let same = |rec: Recommendation| {
+            taken == rec
+                || (taken >= Recommendation::Raise && rec >= Recommendation::Raise)
+                || (rec == Recommendation::Fold
+                    && self.call_price == 0
+                    && taken == Recommendation::Call)
+        };
+        !same(self.recommended.0) || self.chart.is_some_and(|chart| !same(chart))
+    }
+}
+
+impl std::fmt::Display for Decision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: pot {}, to call {}, equity {:.1}%, took {}, recommended {}",
+            self.street,
+            self.pot,
+            self.call_price,
+            self.equity * 100.0,
+            self.taken,
+            self.recommended.0
+        )?;
+        if let Some(chart) = self.chart {
+            write!(f, " (chart: {chart})")?;
+        }
+        if self.ev_loss > 0.0 {
+            write!(f, ", EV loss {:.0}", self.ev_loss)?;
+        }
+        Ok(())
+    }
+}
+
+/// Hero's decisions in a single hand
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandReview {
+    pub hand: HandHistory,
+    pub decisions: Vec<Decision>,
+}
+
+/// Seated players in the order they act after the flop, from the small blind to the button
+fn seat_order(hand: &HandHistory) -> Vec<&str> {
+    let seated = hand
+        .seats
+        .iter()
+        .filter(|s| !s.sitting_out)
+        .collect::<Vec<_>>();
+    let button = seated
+        .iter()
+        .position(|s| s.number == hand.button)
+        .unwrap_or_default();
+    let n = seated.len();
+    (1..=n)
+        .map(|i| seated[(button + i) % n].player.as_str())
+        .collect()
+}
+
+/// Position of a player, counted from the button
+fn position_of(order: &[&str], player: &str) -> Option<Position> {
+    let seat = order.iter().position(|p| *p == player)?;
+    let n = order.len();
+    let positions = Position::with_n_players(n);
+    if n == 2 {
+        return Some([Position::BigBlind, Position::SmallBlind][seat]);
+    }
+    Some(match seat {
+        0 => Position::SmallBlind,
+        1 => Position::BigBlind,
+        i => positions.get(n + 1 - i).copied().unwrap_or(Position::UTG),
+    })
+}
+
+/// Replay a hand and grade every decision the hero made. Only hold'em hands are reviewed, as
+/// ranges only hold two card combinations.
+pub fn review_hand(hand: &HandHistory, tracker: &Tracker) -> Option<HandReview> {
+    let hero = hand.hero.as_deref()?;
+    if hand.variant() != Some(Variant::Holdem) {
+        return None;
+    }
+    let hole = hand.hole_cards.iter().find(|(p, _)| p == hero)?.1.pair()?;
+    let order = seat_order(hand);
+    let position = position_of(&order, hero).unwrap_or_default();
+    let mut stacks = hand
+        .seats
+        .iter()
+        .map(|s| (s.player.as_str(), s.stack))
+        .collect::<HashMap<_, _>>();
+    let mut pot = 0;
+    for (player, post) in &hand.posts {
+        pot += post.amount();
+        if let Some(stack) = stacks.get_mut(player.as_str()) {
+            *stack = stack.saturating_sub(post.amount());
+        }
+    }
+    let mut in_hand = order.clone();
+    let full_board = Board::from_cards(&hand.board)?;
+    let mut street = Street::PreFlop;
+    let mut in_front = hand.street_start(street);
+    let mut acted: Vec<&str> = vec![];
+    let mut raised = false;
+    let mut decisions = vec![];
+    for (i, a) in hand.actions.iter().enumerate() {
+        if a.street != street {
+            street = a.street;
+            in_front = hand.street_start(street);
+            acted.clear();
+        }
+        let player = a.player.as_str();
+        let highest = in_front.values().copied().max().unwrap_or_default();
+        let put_in = in_front.get(player).copied().unwrap_or_default();
+        let stack = stacks.get(player).copied().unwrap_or_default();
+        if player == hero
+            && let Some(board) = full_board.at(street)
+        {
+            let call_price = highest.saturating_sub(put_in).min(stack);
+            let state = DeckState {
+                board,
+                hand: hole.into(),
+                variance: Variance::Normal,
+                dead: CardSet::default(),
+                variant: Variant::Holdem,
+            };
+            // Each opponent's range follows from their statistics and the actions they took
+            let mut equity = 1.0;
+            let mut opponents = vec![];
+            for villain in in_hand.iter().copied().filter(|p| *p != hero) {
+                let stats = tracker.get(villain).copied().unwrap_or_default();
+                let profile = Profile::from(&stats);
+                let mut range = Range::from(HandChart::opponent_expectation_for(&stats));
+                let villain_actions = hand.actions[..i]
+                    .iter()
+                    .filter(|a| a.player == villain)
+                    .map(|a| (a.street, a.action))
+                    .collect::<Vec<_>>();
+                range.narrow(&profile, board, Variant::Holdem, &villain_actions);
+                equity *= state.sampled_strength_against(&range, SAMPLES);
+                opponents.push(range.fold_model(&profile, board, Variant::Holdem));
+            }
+            let spot = ai::Spot {
+                hand: hole.into(),
+                board,
+                hand_strength: equity,
+                call_price,
+                put_in,
+                pot,
+                stack,
+                effective_stack: in_hand
+                    .iter()
+                    .filter(|p| **p != hero)
+                    .filter_map(|p| stacks.get(p).copied())
+                    .max()
+                    .map_or(stack, |villain| villain.min(stack)),
+                blind: hand.big_blind,
+                in_position: in_hand.last() == Some(&hero),
+                left_to_act: in_hand
+                    .iter()
+                    .filter(|p| **p != hero && !acted.contains(p))
+                    .count(),
+                opponents,
+                variant: Variant::Holdem,
+            };
+            let advice = ai::decide(&spot, &ai::BetSize::MENU);
+            let chart = (street == Street::PreFlop && !raised && position != Position::BigBlind)
+                .then(|| position.gto_preflop()[hole]);
+            let taken = match a.action {
+                Action::Fold => spot.fold(),
+                Action::Check | Action::Call(_) => spot.call(),
+                Action::Bet(amount) => spot.raise(amount),
+                Action::Raise(to) => spot.raise(to.saturating_sub(put_in)),
+            };
+            let best_ev = advice.choices.iter().map(|c| c.ev).fold(taken.ev, f64::max);
+            decisions.push(Decision {
+                street,
+                board,
+                pot,
+                call_price,
+                stack,
+                equity,
+                taken: a.action,
+                recommended: (advice.recommendation, advice.amount),
+                chart,
+                ev_loss: best_ev - taken.ev,
+            });
+        }
+        let added = match a.action {
+            Action::Call(amount) | Action::Bet(amount) => amount,
+            Action::Raise(to) => to.saturating_sub(put_in),
+            Action::Fold | Action::Check => 0,
+        };
+        if matches!(a.action, Action::Bet(_) | Action::Raise(_)) && street == Street::PreFlop {
+            raised = true;
+        }
+        if a.action == Action::Fold {
+            in_hand.retain(|p| *p != player);
+        }
+        acted.push(player);
+        *in_front.entry(player).or_default() += added;
+        if let Some(stack) = stacks.get_mut(player) {
+            *stack = stack.saturating_sub(added);
+        }
+        pot += added;
+    }
+    Some(HandReview {
+        hand: hand.clone(),
+        decisions,
+    })
+}
+
+/// A kind of mistake repeated over a session
+#[derive(Debug, Clone, PartialEq)]
+pub struct Leak {
+    pub description: String,
+    pub count: usize,
+    pub ev_loss: f64,
+}
+
+/// Group deviations from the recommendations, biggest total EV loss first
+pub fn leaks(reviews: &[HandReview]) -> Vec<Leak> {
+    let mut leaks: HashMap<String, Leak> = HashMap::new();
+    for decision in reviews.iter().flat_map(|r| &r.decisions) {
+        if !decision.deviates() {
+            continue;
+        }
+        let description = match decision.chart {
+            Some(chart) if decision.taken_recommendation() != chart => format!(
+                "{}: {} instead of opening chart {}",
+                decision.street,
+                decision.taken_recommendation(),
+                chart
+            ),
+            _ => format!(
+                "{}: {} instead of {}",
+                decision.street,
+                decision.taken_recommendation(),
+                decision.recommended.0
+            ),
+        };
+        let leak = leaks.entry(description.clone()).or_insert(Leak {
+            description,
+            count: 0,
+            ev_loss: 0.0,
+        });
+        leak.count += 1;
+        leak.ev_loss += decision.ev_loss;
+    }
+    let mut leaks = leaks.into_values().collect::<Vec<_>>();
+    leaks.sort_by(|a, b| b.ev_loss.total_cmp(&a.ev_loss).then(b.count.cmp(&a.count)));
+    leaks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cards::{Card, HoleCards},
+        history::HERO,
+    };
+
+    fn heads_up(hole: &[Card], variant: Variant) -> HandHistory {
+        let state = DeckState {
+            board: Board::PreFlop,
+            hand: HoleCards::new(hole).unwrap(),
+            variance: Variance::Normal,
+            dead: CardSet::default(),
+            variant,
+        };
+        let fold = PlayerAction {
+            player: HERO.to_string(),
+            street: Street::PreFlop,
+            action: Action::Fold,
+        };
+        HandHistory::heads_up(1, 2, state, false, (99, 98), "Villain", &[fold])
+    }
+
+    /// Folding aces in the small blind goes against the opening chart and shows up as a leak
+    #[test]
+    fn folded_aces() {
+        let review = review_hand(
+            &heads_up(&cards!("Ah", "As"), Variant::Holdem),
+            &Tracker::default(),
+        )
+        .unwrap();
+        let [decision] = &review.decisions[..] else {
+            panic!("expected one decision, got {:?}", review.decisions);
+        };
+        assert_eq!((decision.pot, decision.call_price), (3, 1));
+        assert_eq!(decision.chart, Some(Recommendation::Call));
+        assert!(decision.deviates());
+        let leaks = leaks(&[review]);
+        assert_eq!(leaks.len(), 1);
+        assert_eq!(leaks[0].count, 1);
+        assert!(leaks[0].description.contains("opening chart"));
+        // Only hold'em hands are reviewed
+        let omaha = heads_up(&cards!("Ah", "As", "Kd", "Kc"), Variant::Omaha);
+        assert_eq!(review_hand(&omaha, &Tracker::default()), None);
+    }
+}
@@ -95,14 +95,111 @@ impl IntoIterator for Board {
     }
 }
 
+impl Board {
+    pub fn from_cards(cards: &[Card]) -> Option<Board> {
+        Some(match cards {
+            [] => Board::PreFlop,
+            [a, b, c] => Board::Flop([*a, *b, *c]),
+            [a, b, c, d] => Board::Turn([*a, *b, *c, *d]),
+            [a, b, c, d, e] => Board::River([*a, *b, *c, *d, *e]),
+            _ => return None,
+        })
+    }
+
+    pub fn street(&self) -> Street {
+        match self {
+            Board::PreFlop => Street::PreFlop,
+            Board::Flop(_) => Street::Flop,
+            Board::Turn(_) => Street::Turn,
+            Board::River(_) => Street::River,
+        }
+    }
+
+    /// The cards that were on the board during `street`, if it has been dealt
+    pub fn at(self, street: Street) -> Option<Board> {
+        if street > self.street() {
+            return None;
+        }
+        let cards = self.into_iter().collect::<Vec<_>>();
+        Some(match street {
+            Street::PreFlop => Board::PreFlop,
+            Street::Flop => Board::Flop([cards[0], cards[1], cards[2]]),
+            Street::Turn => Board::Turn([cards[0], cards[1], cards[2], cards[3]]),
+            Street::River => Board::River([cards[0], cards[1], cards[2], cards[3], cards[4]]),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Street {
+    #[default]
+    PreFlop,
+    Flop,
+    Turn,
+    River,
+}
+
+impl std::fmt::Display for Street {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Street::PreFlop => "Pre-flop",
+                Street::Flop => "Flop",
+                Street::Turn => "Turn",
+                Street::River => "River",
+            }
+        )
+    }
+}
+
+impl Street {
+    pub const ALL: [Street; 4] = [Self::PreFlop, Self::Flop, Self::Turn, Self::River];
+}
+
+/// A single betting action taken by a player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Fold,
+    Check,
+    Call(usize),
+    Bet(usize),
+    /// Raise to the given total
+    Raise(usize),
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Fold => write!(f, "fold"),
+            Action::Check => write!(f, "check"),
+            Action::Call(amount) => write!(f, "call {amount}"),
+            Action::Bet(amount) => write!(f, "bet {amount}"),
+            Action::Raise(amount) => write!(f, "raise to {amount}"),
+        }
+    }
+}
+
+/// An action taken by a named player during a hand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerAction {
+    pub player: String,
+    pub street: Street,
+    pub action: Action,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct DeckState {
     /// Cards currently on the board
     pub board: Board,
     /// Cards currently in my hand
-    pub hand: [Card; 2],
+    pub hand: HoleCards,
     /// Expected opponent variance
     pub variance: Variance,
+    /// Folded or exposed cards that can't be dealt
+    pub dead: CardSet,
+    pub variant: Variant,
 }
 
 impl IntoIterator for DeckState {
@@ -120,15 +217,24 @@ impl IntoIterator for DeckState {
 }
 
 impl DeckState {
+    /// Every card that can't be dealt to an opponent or the board, including those missing
+    /// from the variant's deck
+    pub fn known_cards(&self) -> impl Iterator<Item = Card> + use<> {
+        self.into_iter()
+            .chain(self.dead)
+            .chain(self.variant.missing_cards())
+    }
+
     pub fn current_rank(&self) -> Ranking {
-        self.rank_with_hand(self.hand)
+        self.rank_with_hand(self.hand.cards())
     }
 
-    pub fn rank_with_hand(&self, hand: impl IntoIterator<Item = Card>) -> Ranking {
+    pub fn rank_with_hand(&self, hand: &[Card]) -> Ranking {
         assert!(
             self.board != Board::PreFlop,
             "Cannot evaluate hand strength pre-flop"
         );
-        Ranking::from(best_hand_in(hand.into_iter().chain(self.board)))
+        let board = self.board.into_iter().collect::<Vec<_>>();
+        self.variant.best_rank_for(hand, &board)
     }
 }
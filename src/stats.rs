@@ -0,0 +1,299 @@
+use std::{collections::BTreeMap, io, path::Path};
+
+use crate::{range::Profile, state::*};
+
+/// Hands needed before an opponent's statistics are trusted over the defaults
+pub const MIN_HANDS: usize = 20;
+
+/// Running counts of an opponent's actions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpponentStats {
+    pub hands: usize,
+    /// Hands where money was voluntarily put in pre-flop
+    pub vpip: usize,
+    /// Hands raised pre-flop
+    pub pfr: usize,
+    /// Times the opponent faced a single pre-flop raise
+    pub three_bet_chances: usize,
+    pub three_bets: usize,
+    /// Times the opponent faced a continuation bet on the flop
+    pub cbets_faced: usize,
+    pub cbet_folds: usize,
+    /// Bets and raises after the flop
+    pub aggressive: usize,
+    /// Calls after the flop
+    pub passive: usize,
+}
+
+fn ratio(count: usize, sample: usize) -> Option<f64> {
+    (sample > 0).then(|| count as f64 / sample as f64)
+}
+
+impl OpponentStats {
+    pub fn vpip(&self) -> Option<f64> {
+        ratio(self.vpip, self.hands)
+    }
+
+    pub fn pfr(&self) -> Option<f64> {
+        ratio(self.pfr, self.hands)
+    }
+
+    pub fn three_bet(&self) -> Option<f64> {
+        ratio(self.three_bets, self.three_bet_chances)
+    }
+
+    pub fn fold_to_cbet(&self) -> Option<f64> {
+        ratio(self.cbet_folds, self.cbets_faced)
+    }
+
+    /// Ratio of bets and raises to calls after the flop
+    pub fn aggression_factor(&self) -> Option<f64> {
+        match (self.aggressive, self.passive) {
+            (0, 0) => None,
+            (aggressive, 0) => Some(aggressive as f64),
+            (aggressive, passive) => Some(aggressive as f64 / passive as f64),
+        }
+    }
+
+    pub fn is_reliable(&self) -> bool {
+        self.hands >= MIN_HANDS
+    }
+}
+
+impl std::fmt::Display for OpponentStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let percent = |stat: Option<f64>, sample: usize| match stat {
+            Some(stat) => format!("{:.0}% ({sample})", stat * 100.0),
+            None => "-".to_string(),
+        };
+        write!(
+            f,
+            "VPIP {} / PFR {} / 3-bet {} / fold to c-bet {} / AF {}",
+            percent(self.vpip(), self.hands),
+            percent(self.pfr(), self.hands),
+            percent(self.three_bet(), self.three_bet_chances),
+            percent(self.fold_to_cbet(), self.cbets_faced),
+            match self.aggression_factor() {
+                Some(af) => format!("{af:.1} ({})", self.aggressive + self.passive),
+                None => "-".to_string(),
+            }
+        )
+    }
+}
+
+impl From<&OpponentStats> for Profile {
+    fn from(stats: &OpponentStats) -> Self {
+        let mut profile = Profile::default();
+        if !stats.is_reliable() {
+            return profile;
+        }
+        if let Some(fold_to_cbet) = stats.fold_to_cbet() {
+            profile.looseness = 1.0 - fold_to_cbet;
+        } else if let Some(vpip) = stats.vpip() {
+            profile.looseness = vpip.sqrt();
+        }
+        profile.looseness = profile.looseness.clamp(0.05, 0.95);
+        if let Some(af) = stats.aggression_factor() {
+            profile.aggression = af / (1.0 + af);
+        }
+        profile
+    }
+}
+
+/// Statistics for every opponent seen, by name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tracker {
+    pub players: BTreeMap<String, OpponentStats>,
+}
+
+impl Tracker {
+    pub fn get(&self, player: &str) -> Option<&OpponentStats> {
+        self.players.get(player)
+    }
+
+    /// Update statistics from every action taken during a single hand. The `hero` isn't an
+    /// opponent, so their actions only count towards what the others faced.
+    pub fn record_hand(&mut self, actions: &[PlayerAction], hero: Option<&str>) {
+        let mut hand: BTreeMap<&str, OpponentStats> = BTreeMap::new();
+        let mut raises = 0;
+        let mut aggressor: Option<&str> = None;
+        let mut cbet = false;
+        let mut flop_bet = false;
+        for PlayerAction {
+            player,
+            street,
+            action,
+        } in actions
+        {
+            let stats = hand.entry(player).or_default();
+            match street {
+                Street::PreFlop => {
+                    if raises == 1 {
+                        stats.three_bet_chances += 1;
+                    }
+                    match action {
+                        Action::Call(_) => stats.vpip += 1,
+                        Action::Bet(_) | Action::Raise(_) => {
+                            stats.vpip += 1;
+                            stats.pfr += 1;
+                            if raises == 1 {
+                                stats.three_bets += 1;
+                            }
+                            raises += 1;
+                            aggressor = Some(player);
+                        }
+                        _ => (),
+                    }
+                }
+                street => {
+                    if *street == Street::Flop && cbet && aggressor != Some(player) {
+                        stats.cbets_faced += 1;
+                        if *action == Action::Fold {
+                            stats.cbet_folds += 1;
+                        }
+                    }
+                    match action {
+                        Action::Bet(_) | Action::Raise(_) => {
+                            stats.aggressive += 1;
+                            // Only a flop bet by the pre-flop raiser before anyone else has bet
+                            // is a c-bet, and any raise after it ends it
+                            cbet =
+                                *street == Street::Flop && aggressor == Some(player) && !flop_bet;
+                            flop_bet |= *street == Street::Flop;
+                        }
+                        Action::Call(_) => stats.passive += 1,
+                        _ => (),
+                    }
+                }
+            }
+        }
+        for (player, hand) in hand.into_iter().filter(|(player, _)| Some(*player) != hero) {
+            let stats = self.players.entry(player.to_string()).or_default();
+            // Everything but post-flop actions is counted at most once per hand
+            stats.hands += 1;
+            stats.vpip += hand.vpip.min(1);
+            stats.pfr += hand.pfr.min(1);
+            stats.three_bet_chances += hand.three_bet_chances.min(1);
+            stats.three_bets += hand.three_bets.min(1);
+            stats.cbets_faced += hand.cbets_faced.min(1);
+            stats.cbet_folds += hand.cbet_folds.min(1);
+            stats.aggressive += hand.aggressive;
+            stats.passive += hand.passive;
+        }
+    }
+
+    /// Statistics saved in tab separated columns, one opponent per line
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut this = Self::default();
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(this),
+            Err(e) => return Err(e),
+        };
+        for (line_number, line) in text.lines().enumerate() {
+            let invalid = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid opponent statistics on line {}", line_number + 1),
+                )
+            };
+            let mut columns = line.split('\t');
+            let name = columns.next().ok_or_else(invalid)?;
+            let counts = columns
+                .map(|c| c.parse::<usize>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid())?;
+            let [
+                hands,
+                vpip,
+                pfr,
+                three_bet_chances,
+                three_bets,
+                cbets_faced,
+                cbet_folds,
+                aggressive,
+                passive,
+            ] = counts[..]
+            else {
+                return Err(invalid());
+            };
+            this.players.insert(
+                name.to_string(),
+                OpponentStats {
+                    hands,
+                    vpip,
+                    pfr,
+                    three_bet_chances,
+                    three_bets,
+                    cbets_faced,
+                    cbet_folds,
+                    aggressive,
+                    passive,
+                },
+            );
+        }
+        Ok(this)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut text = String::new();
+        for (name, s) in &self.players {
+            text += &format!(
+                "{name}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                s.hands,
+                s.vpip,
+                s.pfr,
+                s.three_bet_chances,
+                s.three_bets,
+                s.cbets_faced,
+                s.cbet_folds,
+                s.aggressive,
+                s.passive
+            );
+        }
+        std::fs::write(path, text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn act(player: &str, street: Street, action: Action) -> PlayerAction {
+        PlayerAction {
+            player: player.to_string(),
+            street,
+            action,
+        }
+    }
+
+    /// A fold to a re-raise after the c-bet isn't a fold to the c-bet, and the hero isn't
+    /// tracked as an opponent
+    #[test]
+    fn record_hand() {
+        let mut tracker = Tracker::default();
+        tracker.record_hand(
+            &[
+                act("Hero", Street::PreFlop, Action::Fold),
+                act("Alice", Street::PreFlop, Action::Raise(6)),
+                act("Bob", Street::PreFlop, Action::Call(6)),
+                act("Bob", Street::Flop, Action::Check),
+                act("Alice", Street::Flop, Action::Bet(8)),
+                act("Bob", Street::Flop, Action::Raise(24)),
+                act("Alice", Street::Flop, Action::Raise(60)),
+                act("Bob", Street::Flop, Action::Fold),
+            ],
+            Some("Hero"),
+        );
+        assert_eq!(tracker.get("Hero"), None);
+        let alice = tracker.get("Alice").unwrap();
+        assert_eq!(
+            (alice.hands, alice.vpip, alice.pfr, alice.aggressive),
+            (1, 1, 1, 2)
+        );
+        let bob = tracker.get("Bob").unwrap();
+        assert_eq!((bob.vpip, bob.pfr, bob.three_bet_chances), (1, 0, 1));
+        assert_eq!((bob.cbets_faced, bob.cbet_folds, bob.aggressive), (1, 0, 1));
+        assert_eq!(bob.fold_to_cbet(), Some(0.0));
+    }
+}
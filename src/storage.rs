@@ -0,0 +1,16 @@
+use std::path::PathBuf;
+
+/// Location of a file in the local data directory, which is created if it does not exist.
+/// Tests keep their files in a temporary directory instead.
+pub fn data_path(name: &str) -> PathBuf {
+    let dir = if cfg!(test) {
+        std::env::temp_dir().join(format!("holdem-test-{}", std::process::id()))
+    } else {
+        match std::env::var_os("HOME") {
+            Some(home) => PathBuf::from(home).join(".local/share/holdem"),
+            None => PathBuf::from("."),
+        }
+    };
+    let _ = std::fs::create_dir_all(&dir);
+    dir.join(name)
+}
@@ -0,0 +1,234 @@
+use itertools::Itertools;
+
+use crate::{
+    cards::*,
+    eval::*,
+    range::{OmahaRange, OpponentRange, Range},
+    rank::*,
+    state::*,
+};
+
+/// Random Omaha hands and boards for each street and each next card, far fewer than for a
+/// single strength as an analysis takes one for every card that can come next
+const OMAHA_STREET_SAMPLES: usize = 1000;
+
+/// Strength against a range after one card that may come next
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NextCard {
+    pub card: Card,
+    pub strength: f64,
+    /// Kind of hand the card makes, when it's a better kind than the hand holds now and than
+    /// the board alone gives every player
+    pub improvement: Option<RankKind>,
+}
+
+/// Kind of hand every player holds from the board alone
+fn board_kind(variant: Variant, board: &[Card]) -> RankKind {
+    if board.len() >= 5 {
+        return variant.best_rank_in(board.iter().copied()).kind();
+    }
+    let groups = board
+        .iter()
+        .map(|c| c.0.high())
+        .counts()
+        .into_values()
+        .sorted_by(|a, b| b.cmp(a))
+        .collect::<Vec<_>>();
+    match groups[..] {
+        [4, ..] => RankKind::FourKind,
+        [3, ..] => RankKind::ThreeKind,
+        [2, 2, ..] => RankKind::TwoPairs,
+        [2, ..] => RankKind::OnePair,
+        _ => RankKind::HighCard,
+    }
+}
+
+/// Report the progress of calculation `part` of `parts` as progress through all of them
+fn part_progress(
+    progress: &impl Fn(Progress),
+    part: usize,
+    parts: usize,
+) -> impl Fn(Progress) + '_ {
+    move |p: Progress| {
+        progress(Progress {
+            done: (part as f64 + p.done) / parts as f64,
+            ..p
+        })
+    }
+}
+
+impl DeckState {
+    /// Strength against `range` for one street or next card of an analysis, estimated from
+    /// fewer samples than `cancellable_strength_against` takes for Omaha hands
+    fn street_strength(
+        &self,
+        range: &OpponentRange,
+        cancel: &CancelToken,
+        progress: &impl Fn(Progress),
+    ) -> Option<f64> {
+        if !self.variant.omaha() {
+            return self.cancellable_strength_against(range, cancel, progress);
+        }
+        let range = match range {
+            OpponentRange::Omaha(range) => *range,
+            OpponentRange::Combos(_) => OmahaRange::default(),
+        };
+        self.sampled_omaha_strength(&range, OMAHA_STREET_SAMPLES, cancel, progress)
+    }
+
+    /// Strength against `range` on each street dealt so far, from preflop to the current one.
+    /// Gives up once `cancel` is set.
+    pub fn strength_by_street(
+        &self,
+        range: &OpponentRange,
+        cancel: &CancelToken,
+        progress: &impl Fn(Progress),
+    ) -> Option<Vec<(Street, f64)>> {
+        let boards = Street::ALL
+            .into_iter()
+            .filter_map(|street| Some((street, self.board.at(street)?)))
+            .collect::<Vec<_>>();
+        boards
+            .iter()
+            .enumerate()
+            .map(|(i, (street, board))| {
+                let state = DeckState {
+                    board: *board,
+                    ..*self
+                };
+                let progress = part_progress(progress, i, boards.len());
+                Some((*street, state.street_strength(range, cancel, &progress)?))
+            })
+            .collect()
+    }
+
+    /// Strength against `range` after each card that can be dealt next on the flop or turn.
+    /// Gives up once `cancel` is set.
+    pub fn next_card_strengths(
+        &self,
+        range: &OpponentRange,
+        cancel: &CancelToken,
+        progress: &impl Fn(Progress),
+    ) -> Option<Vec<NextCard>> {
+        if !matches!(self.board, Board::Flop(_) | Board::Turn(_)) {
+            return Some(vec![]);
+        }
+        let current = self.current_rank();
+        let cards = self.variant.deck_without(self.known_cards());
+        cards
+            .iter()
+            .enumerate()
+            .map(|(i, card)| {
+                let board = self.board.into_iter().chain([*card]).collect::<Vec<_>>();
+                let state = DeckState {
+                    board: Board::from_cards(&board).unwrap(),
+                    ..*self
+                };
+                let progress = part_progress(progress, i, cards.len());
+                let strength = state.street_strength(range, cancel, &progress)?;
+                let rank = state.current_rank();
+                let improved = rank.kind() != current.kind()
+                    && rank > current
+                    && rank.kind() > board_kind(self.variant, &board);
+                Some(NextCard {
+                    card: *card,
+                    strength,
+                    improvement: improved.then_some(rank.kind()),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Next cards grouped by the kind of hand they make, the best improvement first and the cards
+/// that don't improve last. Each group starts with its strongest card.
+pub fn by_improvement(cards: &[NextCard]) -> Vec<(Option<RankKind>, Vec<NextCard>)> {
+    cards
+        .iter()
+        .copied()
+        .sorted_by(|a, b| b.strength.total_cmp(&a.strength))
+        .into_group_map_by(|card| card.improvement)
+        .into_iter()
+        .sorted_by(|a, b| b.0.cmp(&a.0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cards that improve the hand are grouped by the kind of hand they make
+    #[test]
+    fn turn_and_river() {
+        let turn = DeckState {
+            board: Board::from_cards(&cards!("Qh", "Jh", "2c", "3d")).unwrap(),
+            hand: HoleCards::from(cards!("Ah", "Kh")),
+            variance: Variance::Random,
+            dead: CardSet::default(),
+            variant: Variant::Holdem,
+        };
+        let mut range = Range::from(cards!("7s", "6s"));
+        for combo in [cards!("7d", "6d"), cards!("7c", "6c")] {
+            range.set_weight(combo, 1.0);
+        }
+        let range = OpponentRange::Combos(range);
+        let cancel = CancelToken::default();
+
+        let streets = turn.strength_by_street(&range, &cancel, &|_| ()).unwrap();
+        assert_eq!(
+            streets.iter().map(|s| s.0).collect::<Vec<_>>(),
+            [Street::PreFlop, Street::Flop, Street::Turn]
+        );
+        assert_eq!(
+            Some(streets[2].1),
+            turn.cancellable_strength_against(&range, &cancel, &|_| ())
+        );
+
+        let next = turn.next_card_strengths(&range, &cancel, &|_| ()).unwrap();
+        assert_eq!(next.len(), 46);
+        let groups = by_improvement(&next)
+            .into_iter()
+            .map(|(kind, cards)| (kind, cards.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            groups,
+            [
+                (Some(RankKind::RoyalFlush), 1),
+                (Some(RankKind::Flush), 8),
+                (Some(RankKind::Straight), 3),
+                (Some(RankKind::OnePair), 6),
+                (None, 28),
+            ]
+        );
+        let river = DeckState {
+            board: Board::from_cards(&cards!("Qh", "Jh", "2c", "3d", "Th")).unwrap(),
+            ..turn
+        };
+        assert_eq!(
+            river.next_card_strengths(&range, &cancel, &|_| ()),
+            Some(vec![])
+        );
+    }
+
+    /// Omaha streets are estimated against the range given, from the flop back to preflop
+    #[test]
+    fn omaha_streets() {
+        let flop = DeckState {
+            board: Board::from_cards(&cards!("Qh", "Jh", "2c")).unwrap(),
+            hand: HoleCards::new(&cards!("Ah", "Kh", "Th", "9c")).unwrap(),
+            variance: Variance::Random,
+            dead: CardSet::default(),
+            variant: Variant::Omaha,
+        };
+        let range = OpponentRange::Omaha(OmahaRange::default());
+        let streets = flop
+            .strength_by_street(&range, &CancelToken::default(), &|_| ())
+            .unwrap();
+        assert_eq!(
+            streets.iter().map(|s| s.0).collect::<Vec<_>>(),
+            [Street::PreFlop, Street::Flop]
+        );
+        // A royal flush draw with two overcards is well ahead of a random hand
+        assert!(streets[1].1 > 0.6, "{streets:?}");
+    }
+}
@@ -0,0 +1,320 @@
+use itertools::Itertools;
+use rand::seq::SliceRandom;
+
+use crate::{cards::*, eval::HiLoEquity, parse::Parse, rank::*};
+
+/// Games of the stud family, where each player gets their own seven cards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StudGame {
+    #[default]
+    Stud,
+    /// Lowest ace-to-five hand wins
+    Razz,
+    /// The best eight-or-better low wins half the pot
+    StudHiLo,
+}
+
+impl std::fmt::Display for StudGame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                StudGame::Stud => "Stud",
+                StudGame::Razz => "Razz",
+                StudGame::StudHiLo => "Stud8",
+            }
+        )
+    }
+}
+
+impl Parse for StudGame {
+    fn parse(iter: &mut impl Iterator<Item = char>) -> Option<Self> {
+        match iter.collect::<String>().to_lowercase().as_str() {
+            "stud" => Some(StudGame::Stud),
+            "razz" => Some(StudGame::Razz),
+            "stud8" => Some(StudGame::StudHiLo),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StudStreet {
+    Third,
+    Fourth,
+    Fifth,
+    Sixth,
+    Seventh,
+}
+
+impl std::fmt::Display for StudStreet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                StudStreet::Third => "Third street",
+                StudStreet::Fourth => "Fourth street",
+                StudStreet::Fifth => "Fifth street",
+                StudStreet::Sixth => "Sixth street",
+                StudStreet::Seventh => "Seventh street",
+            }
+        )
+    }
+}
+
+impl StudStreet {
+    /// The street on which a player has been dealt `cards` cards
+    pub fn with_cards(cards: usize) -> Option<Self> {
+        Some(match cards {
+            3 => StudStreet::Third,
+            4 => StudStreet::Fourth,
+            5 => StudStreet::Fifth,
+            6 => StudStreet::Sixth,
+            7 => StudStreet::Seventh,
+            _ => return None,
+        })
+    }
+}
+
+/// Cards dealt to one stud player
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StudHand {
+    /// Cards only the player can see
+    pub down: Vec<Card>,
+    /// Cards every player can see
+    pub up: Vec<Card>,
+}
+
+impl StudHand {
+    pub fn cards(&self) -> impl Iterator<Item = Card> + '_ {
+        self.down.iter().chain(&self.up).copied()
+    }
+
+    pub fn street(&self) -> Option<StudStreet> {
+        StudStreet::with_cards(self.down.len() + self.up.len())
+    }
+}
+
+/// Parses down cards and up cards separated by a `/`, such as `AsKd/Qh`
+impl Parse for StudHand {
+    fn parse(iter: &mut impl Iterator<Item = char>) -> Option<Self> {
+        let text = iter.collect::<String>();
+        let (down, up) = text.split_once('/')?;
+        Some(StudHand {
+            down: Vec::parse(&mut down.chars())?,
+            up: Vec::parse(&mut up.chars())?,
+        })
+    }
+}
+
+/// Forced bets and bet sizes of a fixed limit stud game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StudLimits {
+    pub ante: usize,
+    /// Forced bet of the player showing the worst up-card on third street
+    pub bring_in: usize,
+    /// Bet on third and fourth street, and the amount a bring-in is completed to
+    pub small_bet: usize,
+    /// Bet from fifth street on
+    pub big_bet: usize,
+}
+
+impl StudLimits {
+    /// Usual structure for a game with `small_bet`
+    pub fn with_small_bet(small_bet: usize) -> Self {
+        StudLimits {
+            ante: small_bet / 5,
+            bring_in: small_bet.div_ceil(4),
+            small_bet,
+            big_bet: small_bet * 2,
+        }
+    }
+
+    /// Size of a bet or raise on `street`. In Stud a pair showing on fourth street allows
+    /// the big bet.
+    pub fn bet(&self, game: StudGame, street: StudStreet, open_pair: bool) -> usize {
+        match street {
+            StudStreet::Third => self.small_bet,
+            StudStreet::Fourth if open_pair && game != StudGame::Razz => self.big_bet,
+            StudStreet::Fourth => self.small_bet,
+            _ => self.big_bet,
+        }
+    }
+}
+
+/// Face value with aces high or low
+fn face_value(face: Face, ace_high: bool) -> usize {
+    if ace_high { face.high() } else { face.low() }
+}
+
+/// Ordering of up-cards for the bring-in, with suits breaking ties from clubs up to spades
+fn suit_value(suite: Suite) -> usize {
+    match suite {
+        Suite::Club => 0,
+        Suite::Diamond => 1,
+        Suite::Heart => 2,
+        Suite::Spade => 3,
+    }
+}
+
+/// Strength of the up-cards showing, as group sizes then faces of the biggest groups first
+fn showing(up: &[Card], ace_high: bool) -> (Vec<usize>, Vec<usize>) {
+    let groups = up
+        .iter()
+        .map(|c| face_value(c.0, ace_high))
+        .counts()
+        .into_iter()
+        .sorted_by(|a, b| (b.1, b.0).cmp(&(a.1, a.0)))
+        .collect::<Vec<_>>();
+    (
+        groups.iter().map(|g| g.1).collect(),
+        groups.iter().map(|g| g.0).collect(),
+    )
+}
+
+impl StudGame {
+    /// Whether aces count high when ordering up-cards
+    fn ace_high(self) -> bool {
+        self != StudGame::Razz
+    }
+
+    /// Player forced to bring in on third street: the lowest up-card, or the highest in Razz
+    pub fn bring_in(self, hands: &[StudHand]) -> Option<usize> {
+        let card_value = |i: &usize| {
+            let card = hands[*i].up[0];
+            (face_value(card.0, self.ace_high()), suit_value(card.1))
+        };
+        let players = (0..hands.len()).filter(|i| !hands[*i].up.is_empty());
+        match self {
+            StudGame::Razz => players.max_by_key(card_value),
+            _ => players.min_by_key(card_value),
+        }
+    }
+
+    /// Player acting first after third street: the best hand showing, or the lowest in Razz.
+    /// Ties go to the player in the earliest seat.
+    pub fn first_to_act(self, hands: &[StudHand]) -> Option<usize> {
+        let strength = |i: usize| showing(&hands[i].up, self.ace_high());
+        (0..hands.len()).reduce(|best, i| {
+            let better = match self {
+                StudGame::Razz => strength(i) < strength(best),
+                _ => strength(i) > strength(best),
+            };
+            if better { i } else { best }
+        })
+    }
+
+    /// Shares of the pot each player wins with the high and the low hand when every hand is
+    /// shown
+    pub fn pot_shares(self, hands: &[Vec<Card>]) -> Vec<(f64, f64)> {
+        let highs = hands
+            .iter()
+            .map(|h| (self != StudGame::Razz).then(|| Variant::Holdem.best_rank_in(h.clone())))
+            .collect::<Vec<_>>();
+        let lows = hands
+            .iter()
+            .map(|h| match self {
+                StudGame::Stud => None,
+                StudGame::Razz => Low::ace_to_five(h.clone()),
+                StudGame::StudHiLo => Low::find(h.clone()),
+            })
+            .collect::<Vec<_>>();
+        pot_shares(&highs, &lows)
+    }
+
+    /// Estimate the hero's share of the pot from `samples` random deals of the cards still to
+    /// come. Opponents' down cards are unknown, and every visible up-card, along with `dead`
+    /// cards folded by other players, can't be dealt.
+    pub fn equity(
+        self,
+        hero: &StudHand,
+        opponents: &[StudHand],
+        dead: CardSet,
+        samples: usize,
+    ) -> HiLoEquity {
+        let known = std::iter::once(hero.cards().collect::<Vec<_>>())
+            .chain(opponents.iter().map(|o| o.up.clone()))
+            .collect::<Vec<_>>();
+        let mut deck = deck_without(known.iter().flatten().copied().chain(dead));
+        // With too few cards left for everyone, the last card is dealt face up to all
+        let missing = known.iter().map(|k| 7 - k.len().min(7)).sum::<usize>();
+        let community = missing > deck.len();
+        let rng = &mut rand::rng();
+        let mut equity = HiLoEquity::default();
+        for _ in 0..samples {
+            deck.shuffle(rng);
+            let mut cards = deck.iter().copied();
+            let shared = community.then(|| cards.next()).flatten();
+            let hands = known
+                .iter()
+                .map(|k| {
+                    let to_deal = 7 - k.len().min(7) - shared.is_some() as usize;
+                    k.iter()
+                        .copied()
+                        .chain(cards.by_ref().take(to_deal))
+                        .chain(shared)
+                        .collect()
+                })
+                .collect::<Vec<_>>();
+            equity.record(self.pot_shares(&hands)[0]);
+        }
+        equity.averaged(samples)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(down: &str, up: &str) -> StudHand {
+        StudHand::parse(&mut format!("{down}/{up}").chars()).unwrap()
+    }
+
+    /// The worst up-card brings in, and the best hand showing acts first afterwards
+    #[test]
+    fn order_of_action() {
+        let third = [
+            hand("AsKs", "2d"),
+            hand("QhQd", "2c"),
+            hand("3c3d", "Kc"),
+            hand("4c4d", "Ks"),
+            hand("5c5d", "Ah"),
+        ];
+        assert_eq!(StudGame::Stud.bring_in(&third), Some(1));
+        assert_eq!(StudGame::StudHiLo.bring_in(&third), Some(1));
+        // Aces are low in Razz, so the highest king brings in
+        assert_eq!(StudGame::Razz.bring_in(&third), Some(3));
+
+        let fourth = [
+            hand("AsKs", "2d9d"),
+            hand("QhQd", "2c2h"),
+            hand("3c3d", "KcAc"),
+            hand("4c4d", "3s5s"),
+        ];
+        assert_eq!(StudGame::Stud.first_to_act(&fourth), Some(1));
+        assert_eq!(StudGame::Razz.first_to_act(&fourth), Some(3));
+        assert_eq!(StudGame::Stud.bring_in(&[]), None);
+    }
+
+    /// Razz lows count aces low and ignore straights and flushes
+    #[test]
+    fn razz_showdown() {
+        let wheel = cards!("Ah", "2h", "3h", "4h", "5h", "Kd", "Kc").to_vec();
+        let paired = cards!("Ac", "Ad", "2c", "3s", "4h", "Kh", "Ks").to_vec();
+        assert_eq!(
+            Low::ace_to_five(paired.clone()).unwrap().to_string(),
+            "K-4-3-2-A low"
+        );
+        assert_eq!(
+            StudGame::Razz.pot_shares(&[wheel.clone(), paired.clone()]),
+            [(0.0, 1.0), (0.0, 0.0)]
+        );
+        // The straight flush wins the high half in Stud8
+        assert_eq!(
+            StudGame::StudHiLo.pot_shares(&[wheel, paired]),
+            [(0.5, 0.5), (0.0, 0.0)]
+        );
+    }
+}